@@ -1,20 +1,66 @@
 use super::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
 pub(crate) enum Request {
     Status(oneshot::Sender<Status>),
-    Shutdown,
+    ListStations(oneshot::Sender<Result<Vec<StationInfo>>>),
+    Deauthenticate([u8; 6], Option<u16>, oneshot::Sender<Result>),
+    Disassociate([u8; 6], Option<u16>, oneshot::Sender<Result>),
+    SetAndReload(&'static str, String, oneshot::Sender<Result>),
+    SwitchChannel(CsaParams, oneshot::Sender<Result>),
+    Shutdown(oneshot::Sender<()>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Parameters of a channel switch announcement
+pub struct CsaParams {
+    /// Number of beacons announcing the switch before it happens
+    pub count: u8,
+    /// Target frequency in MHz, in the band the access point operates in
+    pub freq: u32,
+    /// Channel width in MHz, eg: 20, 40, 80 or 160. Defaults to the current width.
+    pub bandwidth: Option<u32>,
+    /// Center frequency in MHz, required by hostapd for widths above 20 MHz
+    pub center_freq1: Option<u32>,
+}
+
+impl CsaParams {
+    pub(crate) fn command(&self) -> String {
+        let mut cmd = format!("CHAN_SWITCH {} {}", self.count, self.freq);
+        if let Some(center_freq1) = self.center_freq1 {
+            cmd.push_str(&format!(" center_freq1={center_freq1}"));
+        }
+        if let Some(bandwidth) = self.bandwidth {
+            cmd.push_str(&format!(" bandwidth={bandwidth}"));
+        }
+        cmd
+    }
 }
 
 #[derive(Clone)]
 /// Request client wraps the request events, awaiting oneshot channels when appropriate
 pub struct RequestClient {
-    sender: mpsc::Sender<Request>,
+    pub(crate) sender: mpsc::Sender<Request>,
+    /// How long to wait for the runtime to answer a request
+    timeout: tokio::time::Duration,
 }
 
 impl RequestClient {
     pub(crate) fn new(sender: mpsc::Sender<Request>) -> RequestClient {
-        RequestClient { sender }
+        RequestClient {
+            sender,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+
+    /// Returns a client that gives up on responses after `timeout`, returning
+    /// `Error::RequestTimeout`. Defaults to 10 seconds.
+    pub fn with_timeout(mut self, timeout: tokio::time::Duration) -> RequestClient {
+        self.timeout = timeout;
+        self
     }
 
     async fn send_request(&self, request: Request) -> Result {
@@ -25,24 +71,104 @@ impl RequestClient {
         Ok(())
     }
 
+    async fn await_response<T>(&self, request: oneshot::Receiver<T>) -> Result<T> {
+        tokio::time::timeout(self.timeout, request)
+            .await
+            .map_err(|_| error::Error::RequestTimeout)?
+            .map_err(error::Error::from)
+    }
+
     pub async fn get_status(&self) -> Result<Status> {
         let (response, request) = oneshot::channel();
         self.send_request(Request::Status(response)).await?;
-        Ok(request.await?)
+        self.await_response(request).await
+    }
+
+    /// Lists the stations currently associated with the access point
+    pub async fn list_stations(&self) -> Result<Vec<StationInfo>> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::ListStations(response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// Deauthenticates an associated station, optionally with an 802.11 reason code.
+    /// Fails if the station is not associated.
+    pub async fn deauthenticate(&self, mac: [u8; 6], reason: Option<u16>) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Deauthenticate(mac, reason, response))
+            .await?;
+        self.await_response(request).await?
     }
 
+    /// Disassociates an associated station, optionally with an 802.11 reason code.
+    /// Fails if the station is not associated.
+    pub async fn disassociate(&self, mac: [u8; 6], reason: Option<u16>) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Disassociate(mac, reason, response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Renames the access point. hostapd reloads its configuration to apply it, which
+    /// disconnects associated stations.
+    pub async fn set_ssid(&self, ssid: String) -> Result {
+        crate::sta::validate_ssid(&ssid)?;
+        // ssid2 accepts hex, which avoids quoting spaces, quotes and control characters
+        self.set_and_reload("ssid2", crate::sta::hex_encode(&ssid))
+            .await
+    }
+
+    /// Changes the WPA passphrase of the access point, applying it with a reload. A raw
+    /// key may be given as 64 hex digits instead.
+    pub async fn set_passphrase(&self, psk: String) -> Result {
+        crate::sta::validate_psk(&psk)?;
+        if crate::sta::is_raw_psk(&psk) {
+            self.set_and_reload("wpa_psk", psk).await
+        } else {
+            self.set_and_reload("wpa_passphrase", psk).await
+        }
+    }
+
+    async fn set_and_reload(&self, key: &'static str, value: String) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::SetAndReload(key, value, response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Moves the access point and its stations to another channel of the same band,
+    /// announcing the switch to let stations follow without disconnecting
+    pub async fn switch_channel(&self, csa: CsaParams) -> Result {
+        if crate::sta::frequency_to_channel(csa.freq).is_none() {
+            return Err(error::Error::InvalidFrequency(csa.freq));
+        }
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::SwitchChannel(csa, response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Stops the runtime, returning once it has exited and closed its control sockets
     pub async fn shutdown(&self) -> Result {
-        self.send_request(Request::Shutdown).await
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Shutdown(response)).await?;
+        self.await_response(request).await
     }
 }
 
-#[derive(Debug, Clone)]
-/// Broadcast events, such as a client disconnecting or connecting, may happen at any time.
-pub enum Broadcast {
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Broadcast events, such as a station disconnecting or connecting, may happen at any time.
+pub enum ApEvent {
     Ready,
-    Connected(String),
-    Disconnected(String),
+    /// A station associated, from `AP-STA-CONNECTED`
+    StationConnected([u8; 6]),
+    /// A station left, from `AP-STA-DISCONNECTED`
+    StationDisconnected([u8; 6]),
 }
 
+/// Name of the broadcast events, kept to mirror the station runtime
+pub type Broadcast = ApEvent;
+
 /// Channel for broadcasting events.
-pub type BroadcastReceiver = broadcast::Receiver<Broadcast>;
+pub type BroadcastReceiver = broadcast::Receiver<ApEvent>;
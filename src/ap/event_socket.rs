@@ -8,8 +8,8 @@ pub(crate) struct EventSocket {
 
 #[derive(Debug)]
 pub(crate) enum Event {
-    ApStaConnected(String),
-    ApStaDisconnected(String),
+    ApStaConnected([u8; 6]),
+    ApStaDisconnected([u8; 6]),
 }
 
 pub(crate) type EventReceiver = mpsc::Receiver<Event>;
@@ -64,16 +64,10 @@ impl EventSocket {
                 Ok(n) => {
                     let data_str = std::str::from_utf8(&self.socket_handle.buffer[..n])?.trim_end();
                     debug!("hostapd event: {data_str}");
-                    if let Some(n) = data_str.find("AP-STA-DISCONNECTED") {
-                        let index = n + "AP-STA-DISCONNECTED".len();
-                        let mac = &data_str[index..];
-                        self.send_event(Event::ApStaDisconnected(mac.to_string()))
-                            .await?;
-                    } else if let Some(n) = data_str.find("AP-STA-CONNECTED") {
-                        let index = n + "AP-STA-CONNECTED".len();
-                        let mac = &data_str[index..];
-                        self.send_event(Event::ApStaConnected(mac.to_string()))
-                            .await?;
+                    if let Some(mac) = station_event(data_str, "AP-STA-DISCONNECTED") {
+                        self.send_event(Event::ApStaDisconnected(mac)).await?;
+                    } else if let Some(mac) = station_event(data_str, "AP-STA-CONNECTED") {
+                        self.send_event(Event::ApStaConnected(mac)).await?;
                     }
                 }
                 Err(e) => {
@@ -83,3 +77,16 @@ impl EventSocket {
         }
     }
 }
+
+/// Extracts the station MAC following the event name, eg: `AP-STA-CONNECTED 02:00:00:00:01:00`
+fn station_event(data_str: &str, event: &str) -> Option<[u8; 6]> {
+    let n = data_str.find(event)?;
+    let mac = data_str[n + event.len()..].split_whitespace().next()?;
+    match mac.parse::<crate::sta::Bssid>() {
+        Ok(bssid) => Some(bssid.into()),
+        Err(e) => {
+            warn!("Ignoring {event}: {e}");
+            None
+        }
+    }
+}
@@ -22,20 +22,22 @@ pub struct WifiAp {
     request_receiver: mpsc::Receiver<Request>,
     #[allow(unused)]
     /// Channel for broadcasting alerts
-    broadcast_sender: broadcast::Sender<Broadcast>,
+    broadcast_sender: broadcast::Sender<ApEvent>,
+    /// Acknowledges a shutdown request once the sockets are closed
+    shutdown_ack: Option<oneshot::Sender<()>>,
 }
 
 impl WifiAp {
-    pub async fn run(self) -> Result {
+    pub async fn run(mut self) -> Result {
         info!("Starting Wifi AP process");
 
-        tokio::select!(
-            resp = async move {
+        let resp = tokio::select!(
+            resp = async {
                 // We start up a separate socket for receiving the "unexpected" events that
                 // gets forwarded to us via the event_receiver
                 let (event_receiver, event_socket) = EventSocket::new(&self.socket_path).await?;
                 let socket_handle = SocketHandle::open(&self.socket_path, "mapper_hostapd_sync.sock").await?;
-                self.broadcast_sender.send(Broadcast::Ready)?;
+                self.broadcast_sender.send(ApEvent::Ready)?;
                 tokio::select!(
                     resp = event_socket.run() => resp,
                     resp = self.run_internal(event_receiver, socket_handle) => resp,
@@ -44,11 +46,18 @@ impl WifiAp {
                 println!("{resp:?}");
                 resp
             },
-        )
+        );
+        // the sockets are closed by now, so the caller of shutdown may proceed
+        if let Some(ack) = self.shutdown_ack.take() {
+            if ack.send(()).is_err() {
+                error!("Shutdown response channel closed before response sent");
+            }
+        }
+        resp
     }
 
     async fn run_internal(
-        mut self,
+        &mut self,
         mut event_receiver: EventReceiver,
         mut socket_handle: SocketHandle<2048>,
     ) -> Result {
@@ -71,7 +80,10 @@ impl WifiAp {
                     None => return Err(error::Error::WifiApEventChannelClosed),
                 },
                 EventOrRequest::Request(request) => match request {
-                    Some(Request::Shutdown) => return Ok(()),
+                    Some(Request::Shutdown(ack)) => {
+                        self.shutdown_ack = Some(ack);
+                        return Ok(());
+                    }
                     Some(request) => Self::handle_request(&mut socket_handle, request).await?,
                     None => return Err(error::Error::WifiApRequestChannelClosed),
                 },
@@ -81,17 +93,17 @@ impl WifiAp {
 
     async fn handle_event<const N: usize>(
         _socket_handle: &mut SocketHandle<N>,
-        broadcast_sender: &broadcast::Sender<Broadcast>,
+        broadcast_sender: &broadcast::Sender<ApEvent>,
         event_msg: Event,
     ) -> Result {
         match event_msg {
             Event::ApStaConnected(mac) => {
-                if let Err(e) = broadcast_sender.send(client::Broadcast::Connected(mac)) {
+                if let Err(e) = broadcast_sender.send(ApEvent::StationConnected(mac)) {
                     warn!("error broadcasting: {e}");
                 }
             }
             Event::ApStaDisconnected(mac) => {
-                if let Err(e) = broadcast_sender.send(client::Broadcast::Disconnected(mac)) {
+                if let Err(e) = broadcast_sender.send(ApEvent::StationDisconnected(mac)) {
                     warn!("error broadcasting: {e}");
                 }
             }
@@ -115,8 +127,86 @@ impl WifiAp {
                     error!("Status request response channel closed before response sent");
                 }
             }
-            Request::Shutdown => (), //shutdown is handled at the scope above
+            Request::ListStations(response_channel) => {
+                let stations = Self::list_stations(socket_handle).await;
+                if response_channel.send(stations).is_err() {
+                    error!("ListStations request response channel closed before response sent");
+                }
+            }
+            Request::Deauthenticate(mac, reason, response_channel) => {
+                let resp = Self::kick_station(socket_handle, "DEAUTHENTICATE", mac, reason).await;
+                if response_channel.send(resp).is_err() {
+                    error!("Deauthenticate request response channel closed before response sent");
+                }
+            }
+            Request::Disassociate(mac, reason, response_channel) => {
+                let resp = Self::kick_station(socket_handle, "DISASSOCIATE", mac, reason).await;
+                if response_channel.send(resp).is_err() {
+                    error!("Disassociate request response channel closed before response sent");
+                }
+            }
+            Request::SetAndReload(key, value, response_channel) => {
+                let resp = match socket_handle
+                    .command(format!("SET {key} {value}").as_bytes())
+                    .await
+                {
+                    Ok(()) => socket_handle.command(b"RELOAD").await,
+                    Err(e) => Err(e),
+                };
+                if response_channel.send(resp).is_err() {
+                    error!("SetAndReload request response channel closed before response sent");
+                }
+            }
+            Request::SwitchChannel(csa, response_channel) => {
+                let resp = Self::switch_channel(socket_handle, csa).await;
+                if response_channel.send(resp).is_err() {
+                    error!("SwitchChannel request response channel closed before response sent");
+                }
+            }
+            Request::Shutdown(_) => (), //shutdown is handled at the scope above
         }
         Ok(())
     }
+
+    /// Walks the station table, which hostapd ends with an empty reply
+    async fn list_stations<const N: usize>(
+        socket_handle: &mut SocketHandle<N>,
+    ) -> Result<Vec<StationInfo>> {
+        let mut stations = Vec::new();
+        let mut data_str = socket_handle.query(b"STA-FIRST").await?;
+        let mut command = "STA-FIRST";
+        while let Some(station) = StationInfo::from_response(command, &data_str)? {
+            let next = format!("STA-NEXT {}", station.mac);
+            stations.push(station);
+            data_str = socket_handle.query(next.as_bytes()).await?;
+            command = "STA-NEXT";
+        }
+        Ok(stations)
+    }
+
+    async fn kick_station<const N: usize>(
+        socket_handle: &mut SocketHandle<N>,
+        command: &str,
+        mac: [u8; 6],
+        reason: Option<u16>,
+    ) -> Result {
+        let mut cmd = format!("{command} {}", crate::sta::Bssid(mac));
+        if let Some(reason) = reason {
+            cmd.push_str(&format!(" reason={reason}"));
+        }
+        socket_handle.command(cmd.as_bytes()).await
+    }
+
+    async fn switch_channel<const N: usize>(
+        socket_handle: &mut SocketHandle<N>,
+        csa: CsaParams,
+    ) -> Result {
+        let status = Status::from_response(&socket_handle.query(b"STATUS").await?)?;
+        let current = status.freq.parse::<u32>()?;
+        let band = crate::sta::Band::from_frequency;
+        if band(current) != band(csa.freq) {
+            return Err(error::Error::InvalidFrequency(csa.freq));
+        }
+        socket_handle.command(csa.command().as_bytes()).await
+    }
 }
@@ -28,6 +28,7 @@ impl<const C: usize, const B: usize> WifiSetupGeneric<C, B> {
                 socket_path: PATH_DEFAULT_SERVER.into(),
                 request_receiver,
                 broadcast_sender,
+                shutdown_ack: None,
             },
             request_client,
             broadcast_receiver,
@@ -38,9 +39,20 @@ impl<const C: usize, const B: usize> WifiSetupGeneric<C, B> {
         self.wifi.socket_path = path.into();
     }
 
+    /// Builder form of `set_socket_path`, for sockets outside the default location
+    pub fn control_socket_path<S: Into<std::path::PathBuf>>(mut self, path: S) -> Self {
+        self.set_socket_path(path);
+        self
+    }
+
     pub fn get_broadcast_receiver(&self) -> BroadcastReceiver {
         self.wifi.broadcast_sender.subscribe()
     }
+
+    /// Subscribes to broadcasts as a `Stream`
+    pub fn broadcast_stream(&self) -> stream::BroadcastStream<ApEvent> {
+        self.get_broadcast_receiver().into()
+    }
     pub fn get_request_client(&self) -> RequestClient {
         self.request_client.clone()
     }
@@ -49,3 +61,20 @@ impl<const C: usize, const B: usize> WifiSetupGeneric<C, B> {
         self.wifi
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_capacity_bounds_queued_requests() {
+        let setup = WifiSetupGeneric::<1, 32>::new().unwrap();
+        let client = setup.get_request_client();
+        let status = || Request::Status(oneshot::channel().0);
+        assert!(client.sender.try_send(status()).is_ok());
+        assert!(matches!(
+            client.sender.try_send(status()),
+            Err(mpsc::error::TrySendError::Full(_))
+        ));
+    }
+}
@@ -1,8 +1,13 @@
 use super::{error, Result};
-use serde::{Deserialize, Serialize};
+use crate::error::parse_value;
+use crate::sta::Bssid;
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
 /// Status of the WiFi Station
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Status {
     pub state: String,
     pub phy: String,
@@ -43,10 +48,68 @@ impl Status {
             .add_source(File::from_str(response, FileFormat::Ini))
             .build()
             .map_err(|e| error::Error::ParsingWifiStatus {
-                e,
+                e: Box::new(e),
                 s: response.into(),
             })?;
 
         Ok(config.try_deserialize::<Status>().unwrap())
     }
 }
+
+/// A station associated with the access point, as reported by `STA-FIRST`/`STA-NEXT`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StationInfo {
+    pub mac: Bssid,
+    /// Station flags, eg: `[AUTH][ASSOC][AUTHORIZED]`
+    pub flags: String,
+    /// Seconds since the station connected
+    pub connected_time: Option<u64>,
+    pub rx_bytes: Option<u64>,
+    pub tx_bytes: Option<u64>,
+    /// Signal strength in dBm
+    pub signal: Option<i32>,
+    /// 802.11 capability information field
+    pub capabilities: Option<u16>,
+}
+
+impl StationInfo {
+    /// Parses a station entry, returning `None` for the empty reply that ends the iteration
+    pub fn from_response(command: &'static str, response: &str) -> Result<Option<StationInfo>> {
+        let mut lines = response.lines();
+        let mac = match lines.next().map(str::trim) {
+            Some(mac) if !mac.is_empty() => mac,
+            _ => return Ok(None),
+        };
+        let mut station = StationInfo {
+            mac: parse_value(command, mac, mac)?,
+            ..Default::default()
+        };
+        for line in lines {
+            if let Some((key, value)) = line.split_once('=') {
+                match key {
+                    "flags" => station.flags = value.to_string(),
+                    "connected_time" => {
+                        station.connected_time = Some(parse_value(command, line, value)?)
+                    }
+                    "rx_bytes" => station.rx_bytes = Some(parse_value(command, line, value)?),
+                    "tx_bytes" => station.tx_bytes = Some(parse_value(command, line, value)?),
+                    "signal" => station.signal = Some(parse_value(command, line, value)?),
+                    "capability" => {
+                        let hex = value.trim_start_matches("0x");
+                        station.capabilities =
+                            Some(
+                                u16::from_str_radix(hex, 16).map_err(|e| error::Error::Parse {
+                                    command,
+                                    line: line.to_string(),
+                                    reason: e.to_string(),
+                                })?,
+                            )
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(station))
+    }
+}
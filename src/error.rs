@@ -1,16 +1,37 @@
 use super::*;
 use thiserror::Error;
 
+/// Errors keep their underlying cause as `source()`, eg: for error reporters such as
+/// `anyhow` that print the whole chain. More variants may be added in minor releases.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
     #[error("error parsing wifi status {e}: \n{s}")]
-    ParsingWifiStatus { e: config::ConfigError, s: String },
+    ParsingWifiStatus {
+        #[source]
+        e: Box<config::ConfigError>,
+        s: String,
+    },
+    #[error("error parsing {command} response line {line:?}: {reason}")]
+    Parse {
+        command: &'static str,
+        line: String,
+        reason: String,
+    },
     #[error("unexpected wifi ap response: {0}")]
     UnexpectedWifiApRepsonse(String),
+    #[error("a scan is already in progress")]
+    ScanBusy,
+    #[error("wpa_supplicant reported the scan as failed, ret={ret:?}")]
+    ScanFailed { ret: Option<i32> },
+    #[error("a wps exchange is already in progress")]
+    WpsInProgress,
     #[error("timeout waiting for response")]
     Timeout,
+    #[error("timeout waiting for the runtime to answer the request")]
+    RequestTimeout,
     #[error("did not write all bytes {0}/{1}")]
     DidNotWriteAllBytes(usize, usize),
     #[error("error parsing int: {0}")]
@@ -20,7 +41,7 @@ pub enum Error {
     #[error("recv error: {0}")]
     Recv(#[from] oneshot::error::RecvError),
     #[error("unsolicited socket io error: {0}")]
-    UnsolicitedIoError(std::io::Error),
+    UnsolicitedIoError(#[source] std::io::Error),
     #[error("wifi_ctrl::station internal request channel unexpectedly closed")]
     WifiStationRequestChannelClosed,
     #[error("wifi_ctrl::station internal event channel unexpectedly closed")]
@@ -35,8 +56,164 @@ pub enum Error {
     WifiStaBroadcast(#[from] broadcast::error::SendError<sta::Broadcast>),
     #[error("wifi sta select result")]
     WifiSelect,
+    #[error("invalid control command: {0:?}")]
+    InvalidCommand(String),
+    #[error("invalid bssid {0:?}, expected eg: 02:00:00:00:01:00")]
+    InvalidBssid(String),
+    #[error("bssid {0} not found in scan results")]
+    BssidNotFound(sta::Bssid),
+    #[error("invalid signal threshold: {0} dBm")]
+    InvalidSignalThreshold(i32),
+    #[error("invalid autoscan parameters {0:?}")]
+    InvalidAutoscan(String),
+    #[error("invalid frequency: {0} MHz")]
+    InvalidFrequency(u32),
+    #[error("invalid country code: {0}")]
+    InvalidCountryCode(String),
+    #[error("invalid ssid {0:?}, must be 1 to 32 bytes")]
+    InvalidSsid(String),
+    #[error("invalid psk, must be 8 to 63 printable ascii characters or 64 hex digits")]
+    InvalidPsk,
+    #[error("eap {0} given both as a file and as an engine key id")]
+    ConflictingEapCredential(&'static str),
+    #[error("eap {option} is not used by the {method} method")]
+    UnusedEapOption { method: String, option: String },
+    #[error("invalid eap phase1 options {0:?}")]
+    InvalidEapPhase1(String),
+    #[error("invalid {key} value {value:?}")]
+    InvalidSecurityOverride { key: &'static str, value: String },
+    #[error("conflicting security overrides: {0}")]
+    ConflictingSecurityOverrides(&'static str),
+    #[error("invalid sae group {0}")]
+    InvalidSaeGroup(u16),
+    #[error("invalid wep key {0}, expected 5, 13 or 16 characters or 10, 26 or 32 hex digits")]
+    InvalidWepKey(usize),
+    #[error("invalid wep transmit key index {0}, the key must be set")]
+    InvalidWepKeyIndex(usize),
+    #[error("invalid wps pin: {0}")]
+    InvalidWpsPin(String),
+    #[error("{0} is not supported by the driver")]
+    Unsupported(String),
+    #[error("saving the configuration failed, is update_config=1 set?")]
+    SaveConfigDisabled,
+    #[error("wpa_supplicant did not answer PING")]
+    PingFailed,
+    #[error("the interface is unknown on the global control socket")]
+    UnknownInterface,
+    #[error("control socket disconnected")]
+    ControlSocketDisconnected,
     #[error("timeout opening socket {0}")]
     TimeoutOpeningSocket(String),
     #[error("permission denied opening socket {0}")]
     PermissionDeniedOpeningSocket(String),
+    #[error("control socket not found at {path:?}")]
+    ControlSocketNotFound { path: std::path::PathBuf },
+}
+
+/// Parses one value of a response, reporting the command and line on failure
+pub(crate) fn parse_value<T>(command: &'static str, line: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    T::from_str(value).map_err(|e| Error::Parse {
+        command,
+        line: line.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn io_error_converts_and_keeps_source() {
+        let error = Error::from(std::io::Error::new(std::io::ErrorKind::NotFound, "gone"));
+        assert!(matches!(error, Error::Io(_)));
+        assert_eq!(error.source().unwrap().to_string(), "gone");
+    }
+
+    #[test]
+    fn parse_int_error_converts_and_keeps_source() {
+        let cause = "x".parse::<u8>().unwrap_err();
+        let error = Error::from(cause.clone());
+        assert!(matches!(error, Error::ParseInt(_)));
+        assert_eq!(error.source().unwrap().to_string(), cause.to_string());
+    }
+
+    #[test]
+    fn utf8_error_converts_and_keeps_source() {
+        let bytes = vec![b'a', 0xff];
+        let cause = std::str::from_utf8(&bytes).unwrap_err();
+        let error = Error::from(cause);
+        assert!(matches!(error, Error::Utf8Parse(_)));
+        assert_eq!(error.source().unwrap().to_string(), cause.to_string());
+    }
+
+    #[test]
+    fn recv_error_converts_and_keeps_source() {
+        let (sender, receiver) = oneshot::channel::<()>();
+        drop(sender);
+        let error = Error::from(receiver.blocking_recv().unwrap_err());
+        assert!(matches!(error, Error::Recv(_)));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn ap_broadcast_error_converts_and_keeps_source() {
+        let (sender, receiver) = broadcast::channel(1);
+        drop(receiver);
+        let error = Error::from(sender.send(ap::Broadcast::Ready).unwrap_err());
+        assert!(matches!(error, Error::WifiApBroadcast(_)));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn sta_broadcast_error_converts_and_keeps_source() {
+        let (sender, receiver) = broadcast::channel(1);
+        drop(receiver);
+        let error = Error::from(sender.send(sta::Broadcast::Ready).unwrap_err());
+        assert!(matches!(error, Error::WifiStaBroadcast(_)));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn wrapped_causes_are_sources() {
+        let error = Error::ParsingWifiStatus {
+            e: Box::new(config::ConfigError::Message("bad".into())),
+            s: "wpa_state".into(),
+        };
+        assert_eq!(error.source().unwrap().to_string(), "bad");
+        let cause = std::io::Error::new(std::io::ErrorKind::BrokenPipe, "closed");
+        let error = Error::UnsolicitedIoError(cause);
+        assert_eq!(error.source().unwrap().to_string(), "closed");
+    }
+
+    #[test]
+    fn errors_without_cause_have_no_source() {
+        assert!(Error::WifiStationRequestChannelClosed.source().is_none());
+        assert!(Error::WifiStationEventChannelClosed.source().is_none());
+        assert!(Error::Timeout.source().is_none());
+    }
+
+    #[test]
+    fn parse_value_reports_command_and_line() {
+        let line = "freq=abc";
+        let error = parse_value::<u32>("STATUS", line, "abc").unwrap_err();
+        match error {
+            Error::Parse {
+                command,
+                line,
+                reason,
+            } => {
+                assert_eq!(command, "STATUS");
+                assert_eq!(line, "freq=abc");
+                assert!(!reason.is_empty());
+            }
+            other => panic!("unexpected error {other:?}"),
+        }
+        assert_eq!(parse_value::<u32>("STATUS", line, "2412").unwrap(), 2412);
+    }
 }
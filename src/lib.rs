@@ -13,6 +13,7 @@
 #![doc(test(attr(allow(unused_variables), deny(warnings))))]
 
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc, oneshot};
 
@@ -20,12 +21,20 @@ use tokio::sync::{broadcast, mpsc, oneshot};
 pub mod ap;
 /// Crate-wide error types
 pub mod error;
+/// Scriptable control socket for tests
+#[cfg(feature = "test-util")]
+pub mod mock;
 /// WiFi Station (network client) runtime and types
 pub mod sta;
+/// Stream adapters for the broadcast channels
+pub mod stream;
 
 pub(crate) mod socket_handle;
 
 use socket_handle::SocketHandle;
 pub type Result<T = ()> = std::result::Result<T, error::Error>;
 
+/// How long a `RequestClient` waits for the runtime to answer unless configured otherwise
+const DEFAULT_REQUEST_TIMEOUT: tokio::time::Duration = tokio::time::Duration::from_secs(10);
+
 use log::{debug, error, info, warn};
@@ -0,0 +1,311 @@
+use super::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::net::UnixDatagram;
+use tokio::sync::watch;
+
+/// Reply to a command, followed by the events it causes
+#[derive(Debug, Clone)]
+struct MockReply {
+    reply: String,
+    events: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    replies: HashMap<String, MockReply>,
+    /// Every command received, in order
+    commands: Vec<String>,
+    /// Sockets which sent `ATTACH` and receive events
+    attached: Vec<PathBuf>,
+    /// Events sent while no socket was attached, delivered on the next `ATTACH`
+    held_events: Vec<String>,
+}
+
+/// A wpa_supplicant stand-in serving the control socket protocol, for testing code built on
+/// `RequestClient` without a real supplicant. Replies to commands are scripted with `reply`,
+/// events are injected with `event`. Commands without a scripted reply are answered with
+/// `OK`, except for a few defaults: `PING`, `STATUS`, `SCAN_RESULTS`, `LIST_NETWORKS` and
+/// `SCAN`, which completes immediately.
+///
+/// The socket lives in a temporary directory removed once the mock is dropped. Since
+/// hostapd speaks the same protocol, the mock can also back the access point runtime.
+pub struct MockSupplicant {
+    /// Temporary directory for the socket, removed on drop
+    #[allow(unused)]
+    tmp_dir: tempfile::TempDir,
+    path: PathBuf,
+    socket: Arc<UnixDatagram>,
+    state: Arc<Mutex<MockState>>,
+    attached: watch::Receiver<usize>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockSupplicant {
+    /// Binds the control socket of the interface `wlan0` and starts serving it on the
+    /// current Tokio runtime
+    pub fn new() -> Result<MockSupplicant> {
+        let tmp_dir = tempfile::tempdir()?;
+        let path = tmp_dir.path().join("wlan0");
+        let socket = Arc::new(UnixDatagram::bind(&path)?);
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let (attached_sender, attached) = watch::channel(0);
+        let task = tokio::spawn(Self::serve(socket.clone(), state.clone(), attached_sender));
+        let mock = MockSupplicant {
+            tmp_dir,
+            path,
+            socket,
+            state,
+            attached,
+            task,
+        };
+        mock.reply("PING", "PONG");
+        mock.reply("STATUS", "wpa_state=DISCONNECTED");
+        mock.reply(
+            "SCAN_RESULTS",
+            "bssid / frequency / signal level / flags / ssid",
+        );
+        mock.reply("LIST_NETWORKS", "network id / ssid / bssid / flags");
+        mock.reply_with_events("SCAN", "OK", &["CTRL-EVENT-SCAN-RESULTS"]);
+        Ok(mock)
+    }
+
+    /// Path of the control socket, eg: for `WifiSetup::control_socket_path`
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// A station setup controlling the mock
+    pub fn station_setup(&self) -> Result<sta::WifiSetup> {
+        Ok(sta::WifiSetup::new()?.control_socket_path(&self.path))
+    }
+
+    /// Answers `command` with `reply` from now on. Commands are matched exactly, eg:
+    /// `GET_NETWORK 0 ssid`.
+    pub fn reply(&self, command: &str, reply: &str) {
+        self.reply_with_events(command, reply, &[]);
+    }
+
+    /// Answers `command` with `reply`, then sends the events, eg: `CTRL-EVENT-CONNECTED`
+    /// after `SELECT_NETWORK 0`
+    pub fn reply_with_events(&self, command: &str, reply: &str, events: &[&str]) {
+        let reply = MockReply {
+            reply: reply.to_string(),
+            events: events.iter().map(|event| event.to_string()).collect(),
+        };
+        self.lock().replies.insert(command.to_string(), reply);
+    }
+
+    /// Sends an event, eg: `CTRL-EVENT-DISCONNECTED bssid=02:00:00:00:01:00 reason=3`, to
+    /// the attached event sockets. Events sent before the runtime attached are held until
+    /// it does, as are those scripted with `reply_with_events`.
+    pub async fn event(&self, event: &str) -> Result {
+        let attached = {
+            let mut state = self.lock();
+            if state.attached.is_empty() {
+                state.held_events.push(event.to_string());
+            }
+            state.attached.clone()
+        };
+        Self::send_event(&self.socket, &attached, event).await
+    }
+
+    /// Waits for the runtime to attach its event socket
+    pub async fn wait_attached(&self) {
+        // only fails once the mock stopped serving, when there is nothing left to wait for
+        let _ = self
+            .attached
+            .clone()
+            .wait_for(|attached| *attached > 0)
+            .await;
+    }
+
+    /// Commands received so far, including the runtime's own such as `ATTACH` and `PING`
+    pub fn commands(&self) -> Vec<String> {
+        self.lock().commands.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    async fn send_event(socket: &UnixDatagram, attached: &[PathBuf], event: &str) -> Result {
+        let event = format!("<3>{event}");
+        for path in attached {
+            socket.send_to(event.as_bytes(), path).await?;
+        }
+        Ok(())
+    }
+
+    async fn serve(
+        socket: Arc<UnixDatagram>,
+        state: Arc<Mutex<MockState>>,
+        attached_sender: watch::Sender<usize>,
+    ) {
+        let mut buffer = [0; 4096];
+        loop {
+            let (n, addr) = match socket.recv_from(&mut buffer).await {
+                Ok(received) => received,
+                Err(e) => {
+                    warn!("Mock supplicant receive error: {e}");
+                    return;
+                }
+            };
+            let Some(peer) = addr.as_pathname().map(Path::to_path_buf) else {
+                warn!("Mock supplicant ignoring unnamed socket");
+                continue;
+            };
+            let command = String::from_utf8_lossy(&buffer[..n]).to_string();
+            let (reply, events, attached) = {
+                let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+                state.commands.push(command.clone());
+                let mut held_events = Vec::new();
+                match command.as_str() {
+                    "ATTACH" => {
+                        state.attached.push(peer.clone());
+                        held_events = std::mem::take(&mut state.held_events);
+                        attached_sender.send_replace(state.attached.len());
+                    }
+                    "DETACH" => {
+                        state.attached.retain(|path| *path != peer);
+                        attached_sender.send_replace(state.attached.len());
+                    }
+                    _ => (),
+                }
+                let reply = state.replies.get(&command).cloned().unwrap_or(MockReply {
+                    reply: "OK".into(),
+                    events: Vec::new(),
+                });
+                held_events.extend(reply.events);
+                if state.attached.is_empty() {
+                    state.held_events.append(&mut held_events);
+                }
+                (reply.reply, held_events, state.attached.clone())
+            };
+            if let Err(e) = socket.send_to(reply.as_bytes(), &peer).await {
+                debug!("Mock supplicant reply to {peer:?} failed: {e}");
+            }
+            for event in events {
+                if let Err(e) = Self::send_event(&socket, &attached, &event).await {
+                    debug!("Mock supplicant event {event:?} failed: {e}");
+                }
+            }
+        }
+    }
+}
+
+impl Drop for MockSupplicant {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_station(mock: &MockSupplicant) -> (sta::RequestClient, sta::BroadcastReceiver) {
+        let setup = mock.station_setup().unwrap();
+        let client = setup.get_request_client();
+        let broadcast_receiver = setup.get_broadcast_receiver();
+        tokio::spawn(setup.complete().run());
+        (client, broadcast_receiver)
+    }
+
+    /// Next broadcast other than the runtime's own progress reports
+    async fn next_broadcast(receiver: &mut sta::BroadcastReceiver) -> sta::Broadcast {
+        loop {
+            let broadcast =
+                tokio::time::timeout(tokio::time::Duration::from_secs(5), receiver.recv())
+                    .await
+                    .expect("timeout waiting for broadcast")
+                    .unwrap();
+            match broadcast {
+                sta::Broadcast::Ready | sta::Broadcast::ConnectionProgress(_) => (),
+                broadcast => return broadcast,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn wrong_psk_answers_select_and_broadcasts() {
+        let mock = MockSupplicant::new().unwrap();
+        mock.reply_with_events(
+            "SELECT_NETWORK 0",
+            "OK",
+            &["CTRL-EVENT-SSID-TEMP-DISABLED id=0 ssid=\"home\" auth_failures=1 duration=10 reason=WRONG_KEY"],
+        );
+        let (client, mut broadcast_receiver) = spawn_station(&mock);
+        mock.wait_attached().await;
+
+        let result = client.select_network(0).await.unwrap();
+        assert!(matches!(result, sta::SelectResult::WrongPsk));
+        assert_eq!(
+            next_broadcast(&mut broadcast_receiver).await,
+            sta::Broadcast::WrongPsk
+        );
+        assert!(mock.commands().contains(&"SELECT_NETWORK 0".to_string()));
+    }
+
+    #[tokio::test]
+    async fn disconnect_waits_for_the_event() {
+        let mock = MockSupplicant::new().unwrap();
+        mock.reply("STATUS", "wpa_state=COMPLETED");
+        mock.reply_with_events(
+            "DISCONNECT",
+            "OK",
+            &["CTRL-EVENT-DISCONNECTED bssid=02:00:00:00:01:00 reason=3 locally_generated=1"],
+        );
+        let (client, mut broadcast_receiver) = spawn_station(&mock);
+        mock.wait_attached().await;
+
+        client.disconnect().await.unwrap();
+        match next_broadcast(&mut broadcast_receiver).await {
+            sta::Broadcast::Disconnected(info) => {
+                assert_eq!(info.bssid.to_string(), "02:00:00:00:01:00");
+                assert_eq!(info.reason_code, 3);
+                assert!(info.locally_generated);
+            }
+            other => panic!("unexpected broadcast {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn second_wps_pbc_is_rejected() {
+        let mock = MockSupplicant::new().unwrap();
+        let (client, _broadcast_receiver) = spawn_station(&mock);
+        mock.wait_attached().await;
+
+        let first = tokio::spawn({
+            let client = client.clone();
+            async move { client.wps_pbc().await }
+        });
+        while !mock.commands().contains(&"WPS_PBC".to_string()) {
+            tokio::task::yield_now().await;
+        }
+        assert!(matches!(
+            client.wps_pbc().await,
+            Err(error::Error::WpsInProgress)
+        ));
+        mock.event("WPS-SUCCESS").await.unwrap();
+        assert!(matches!(first.await.unwrap(), Ok(sta::WpsResult::Success)));
+    }
+
+    #[tokio::test]
+    async fn events_before_attach_are_held() {
+        let mock = MockSupplicant::new().unwrap();
+        mock.event("CTRL-EVENT-DISCONNECTED bssid=02:00:00:00:01:00 reason=15")
+            .await
+            .unwrap();
+        let (_client, mut broadcast_receiver) = spawn_station(&mock);
+        mock.wait_attached().await;
+
+        match next_broadcast(&mut broadcast_receiver).await {
+            sta::Broadcast::Disconnected(info) => assert_eq!(info.reason_code, 15),
+            other => panic!("unexpected broadcast {other:?}"),
+        }
+    }
+}
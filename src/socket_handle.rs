@@ -24,10 +24,10 @@ impl<const N: usize> SocketHandle<N> {
         let socket_debug = &format!("{path:?}");
         // loop around waiting for the socket for up to 5 minutes
         let socket = tokio::select!(
-            resp = async move  {
+            resp = async {
                 let mut loop_count = 0;
                 let s: Result<UnixDatagram> = loop {
-                    match socket.connect(&path) {
+                    match socket.connect(path.as_ref()) {
                         Ok(()) => break Ok(socket),
                         Err(e) => {
                             // if socket is there but permission denied, fail fast
@@ -44,9 +44,13 @@ impl<const N: usize> SocketHandle<N> {
                 };
                 s
             } => resp,
-            _ = async move {
+            _ = async {
                 tokio::time::sleep(tokio::time::Duration::from_secs(60*RETRY_MINUTES)).await;
-            } => Err(error::Error::TimeoutOpeningSocket(socket_debug.to_string())),
+            } => if path.as_ref().exists() {
+                Err(error::Error::TimeoutOpeningSocket(socket_debug.to_string()))
+            } else {
+                Err(error::Error::ControlSocketNotFound { path: path.as_ref().to_path_buf() })
+            },
         )?;
 
         Ok(Self {
@@ -56,6 +60,31 @@ impl<const N: usize> SocketHandle<N> {
         })
     }
 
+    /// Opens the socket with a single connection attempt, failing immediately if the
+    /// server isn't there
+    pub fn try_open<P>(path: P, label: &str) -> Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let tmp_dir = tempfile::tempdir()?;
+        let connect_from = tmp_dir.path().join(label);
+        let socket = UnixDatagram::bind(connect_from)?;
+        socket.connect(path.as_ref()).map_err(|e| {
+            if e.kind() == ErrorKind::NotFound {
+                error::Error::ControlSocketNotFound {
+                    path: path.as_ref().to_path_buf(),
+                }
+            } else {
+                e.into()
+            }
+        })?;
+        Ok(Self {
+            tmp_dir,
+            socket,
+            buffer: [0; N],
+        })
+    }
+
     pub async fn command(&mut self, cmd: &[u8]) -> Result {
         let n = self.socket.send(cmd).await?;
         if n != cmd.len() {
@@ -64,6 +93,31 @@ impl<const N: usize> SocketHandle<N> {
         self.expect_ok_with_default_timeout().await
     }
 
+    /// Sends a command and returns the trimmed response text, whatever it is
+    pub async fn request(&mut self, cmd: &[u8]) -> Result<String> {
+        let n = self.socket.send(cmd).await?;
+        if n != cmd.len() {
+            return Err(error::Error::DidNotWriteAllBytes(n, cmd.len()));
+        }
+        tokio::select!(
+            resp = self.socket.recv(&mut self.buffer) => {
+                let n = resp?;
+                Ok(std::str::from_utf8(&self.buffer[..n])?.trim_end().to_string())
+            },
+            _ = tokio::time::sleep(tokio::time::Duration::from_secs(1)) => Err(error::Error::Timeout)
+        )
+    }
+
+    /// Sends a command that replies with data, returning a `FAIL` response as an error
+    pub async fn query(&mut self, cmd: &[u8]) -> Result<String> {
+        let data_str = self.request(cmd).await?;
+        if data_str.starts_with("FAIL") {
+            Err(error::Error::UnexpectedWifiApRepsonse(data_str))
+        } else {
+            Ok(data_str)
+        }
+    }
+
     async fn expect_ok(&mut self) -> Result {
         match self.socket.recv(&mut self.buffer).await {
             Ok(n) => {
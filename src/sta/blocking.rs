@@ -0,0 +1,143 @@
+use super::*;
+use tokio::runtime;
+
+fn current_thread_runtime() -> Result<runtime::Runtime> {
+    Ok(runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?)
+}
+
+impl WifiStation {
+    /// Runs the station on a runtime of its own, blocking the calling thread until it exits.
+    /// Typically called from a dedicated thread, eg: `std::thread::spawn(|| station.run_blocking())`
+    pub fn run_blocking(self) -> Result {
+        current_thread_runtime()?.block_on(self.run())
+    }
+}
+
+/// Synchronous wrapper around `RequestClient` for code that doesn't run inside Tokio.
+/// Each call is driven to completion on an internal current-thread runtime, so it must not
+/// be used from within an async context.
+pub struct BlockingClient {
+    client: RequestClient,
+    runtime: runtime::Runtime,
+}
+
+impl BlockingClient {
+    pub fn new(client: RequestClient) -> Result<BlockingClient> {
+        Ok(BlockingClient {
+            client,
+            runtime: current_thread_runtime()?,
+        })
+    }
+
+    /// The async client being wrapped
+    pub fn client(&self) -> &RequestClient {
+        &self.client
+    }
+
+    pub fn interface(&self) -> Option<&str> {
+        self.client.interface()
+    }
+
+    pub fn get_scan(&self) -> Result<Arc<Vec<ScanResult>>> {
+        self.runtime.block_on(self.client.get_scan())
+    }
+
+    pub fn get_scan_outcome(&self) -> Result<ScanOutcome> {
+        self.runtime.block_on(self.client.get_scan_outcome())
+    }
+
+    pub fn get_scan_grouped(&self) -> Result<Vec<ScanGroup>> {
+        self.runtime.block_on(self.client.get_scan_grouped())
+    }
+
+    pub fn get_networks(&self) -> Result<Vec<NetworkResult>> {
+        self.runtime.block_on(self.client.get_networks())
+    }
+
+    pub fn get_status(&self) -> Result<Result<Status>> {
+        self.runtime.block_on(self.client.get_status())
+    }
+
+    pub fn signal_poll(&self) -> Result<SignalPoll> {
+        self.runtime.block_on(self.client.signal_poll())
+    }
+
+    pub fn add_network(&self) -> Result<usize> {
+        self.runtime.block_on(self.client.add_network())
+    }
+
+    pub fn add_and_configure(&self, spec: NetworkSpec) -> Result<usize> {
+        self.runtime.block_on(self.client.add_and_configure(spec))
+    }
+
+    pub fn set_network_psk(&self, network_id: usize, psk: String) -> Result {
+        self.runtime
+            .block_on(self.client.set_network_psk(network_id, psk))
+    }
+
+    pub fn set_network_ssid(&self, network_id: usize, ssid: String) -> Result {
+        self.runtime
+            .block_on(self.client.set_network_ssid(network_id, ssid))
+    }
+
+    pub fn get_network_var(&self, network_id: usize, key: &str) -> Result<String> {
+        self.runtime
+            .block_on(self.client.get_network_var(network_id, key))
+    }
+
+    pub fn save_config(&self) -> Result {
+        self.runtime.block_on(self.client.save_config())
+    }
+
+    pub fn remove_network(&self, network_id: usize) -> Result {
+        self.runtime
+            .block_on(self.client.remove_network(network_id))
+    }
+
+    pub fn enable_network(&self, network_id: usize) -> Result {
+        self.runtime
+            .block_on(self.client.enable_network(network_id))
+    }
+
+    pub fn disable_network(&self, network_id: usize) -> Result {
+        self.runtime
+            .block_on(self.client.disable_network(network_id))
+    }
+
+    pub fn select_network(&self, network_id: usize) -> Result<SelectResult> {
+        self.runtime
+            .block_on(self.client.select_network(network_id))
+    }
+
+    pub fn connect(
+        &self,
+        ssid: &str,
+        psk: Option<&str>,
+        timeout: tokio::time::Duration,
+    ) -> Result<ConnectOutcome> {
+        self.runtime
+            .block_on(self.client.connect(ssid, psk, timeout))
+    }
+
+    pub fn disconnect(&self) -> Result {
+        self.runtime.block_on(self.client.disconnect())
+    }
+
+    pub fn reconnect(&self) -> Result {
+        self.runtime.block_on(self.client.reconnect())
+    }
+
+    pub fn ping(&self) -> Result {
+        self.runtime.block_on(self.client.ping())
+    }
+
+    pub fn raw_command(&self, cmd: &str) -> Result<String> {
+        self.runtime.block_on(self.client.raw_command(cmd))
+    }
+
+    pub fn shutdown(&self) -> Result {
+        self.runtime.block_on(self.client.shutdown())
+    }
+}
@@ -1,16 +1,46 @@
 use super::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Use a reference counter since ScanResults may be sent to many clients at once
 pub type ScanResults = Arc<Vec<ScanResult>>;
 
+#[derive(Debug, Clone)]
+/// Results of a scan along with whether it ran to completion
+pub struct ScanOutcome {
+    pub results: ScanResults,
+    /// False when wpa_supplicant reported the scan as failed, in which case `results` holds
+    /// whatever it had cached and may be missing networks
+    pub complete: bool,
+    /// The `ret=` code of a failed scan, eg: -16 when the driver was busy
+    pub failure_code: Option<i32>,
+}
+
+impl ScanOutcome {
+    /// The results of a complete scan, failing as `get_scan` does otherwise
+    fn complete_results(self) -> Result<ScanResults> {
+        match self.complete {
+            true => Ok(self.results),
+            false => Err(error::Error::ScanFailed {
+                ret: self.failure_code,
+            }),
+        }
+    }
+}
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 /// Result from selecting a network, including a success or a specific failure (eg: incorect psk)
 pub enum SelectResult {
     Success,
     WrongPsk,
     NotFound,
-    PendingSelect,
+    /// Another selection, of `network_id`, is still awaiting its outcome
+    PendingSelect {
+        network_id: usize,
+    },
     InvalidNetworkId,
+    BssidNotFound,
 }
 
 use std::fmt;
@@ -20,44 +50,858 @@ impl fmt::Display for SelectResult {
             SelectResult::Success => "success",
             SelectResult::WrongPsk => "wrong_psk",
             SelectResult::NotFound => "network_not_found",
-            SelectResult::PendingSelect => "select_already_pending",
+            SelectResult::PendingSelect { .. } => "select_already_pending",
             SelectResult::InvalidNetworkId => "invalid_network_id",
+            SelectResult::BssidNotFound => "bssid_not_found",
         };
         write!(f, "{s}")
     }
 }
 
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Result from a WPS exchange
+pub enum WpsResult {
+    Success,
+    Timeout,
+    Overlap,
+    Failed,
+}
+
+impl fmt::Display for WpsResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            WpsResult::Success => "success",
+            WpsResult::Timeout => "timeout",
+            WpsResult::Overlap => "pbc_overlap",
+            WpsResult::Failed => "failed",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Outcome of `RequestClient::connect`
+pub enum ConnectOutcome {
+    Connected,
+    WrongPsk,
+    NotFound,
+    Timeout,
+}
+
+impl fmt::Display for ConnectOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ConnectOutcome::Connected => "connected",
+            ConnectOutcome::WrongPsk => "wrong_psk",
+            ConnectOutcome::NotFound => "network_not_found",
+            ConnectOutcome::Timeout => "timeout",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// IANA Group Description values usable with SAE: the MODP groups 1, 2, 5, 14-18 and
+/// 22-24, and the elliptic curve groups 19-21, 25, 26 and 28-30
+const SAE_GROUPS: [u16; 19] = [
+    1, 2, 5, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 28, 29, 30,
+];
+
+/// WPS walk time as defined by the WPS spec
+const WPS_TIMEOUT: tokio::time::Duration = tokio::time::Duration::from_secs(120);
+
 #[derive(Debug)]
 pub(crate) enum Request {
     Status(oneshot::Sender<Result<Status>>),
-    Networks(oneshot::Sender<Vec<NetworkResult>>),
-    Scan(oneshot::Sender<ScanResults>),
+    Networks(oneshot::Sender<Result<Vec<NetworkResult>>>),
+    Scan(ScanParams, oneshot::Sender<Result<ScanOutcome>>),
+    ScanInProgress(oneshot::Sender<bool>),
     AddNetwork(oneshot::Sender<usize>),
-    SetNetwork(usize, SetNetwork),
-    SaveConfig,
+    SetNetwork(usize, SetNetwork, oneshot::Sender<Result>),
+    AddConfiguredNetwork(Vec<SetNetwork>, bool, oneshot::Sender<Result<usize>>),
+    GetNetwork(usize, String, oneshot::Sender<Result<String>>),
+    SaveConfig(oneshot::Sender<Result>),
     RemoveNetwork(usize),
+    RemoveAllNetworks(oneshot::Sender<Result<usize>>),
+    EnableNetwork(usize),
+    DisableNetwork(usize),
     SelectNetwork(usize, oneshot::Sender<SelectResult>),
-    Shutdown,
+    SelectBssid(usize, Bssid, oneshot::Sender<SelectResult>),
+    Roam(Bssid, oneshot::Sender<Result>),
+    Disconnect(oneshot::Sender<Result>),
+    Reconnect(oneshot::Sender<Result>),
+    Reassociate(oneshot::Sender<Result>),
+    Ping(oneshot::Sender<Result>),
+    Reconfigure(oneshot::Sender<Result>),
+    WpsPbc(oneshot::Sender<Result<WpsResult>>),
+    WpsPin(Option<String>, oneshot::Sender<Result<String>>),
+    SignalPoll(oneshot::Sender<Result<SignalPoll>>),
+    PktcntPoll(oneshot::Sender<Result<LinkStats>>),
+    Mib(oneshot::Sender<Result<Mib>>),
+    ConnectedSince(oneshot::Sender<Option<std::time::Instant>>),
+    P2pPeers(oneshot::Sender<Result<Vec<P2pPeer>>>),
+    Raw(String, oneshot::Sender<Result<String>>),
+    Bss(BssSelector, oneshot::Sender<Result<Option<BssInfo>>>),
+    Set(String, String, oneshot::Sender<Result>),
+    Get(String, oneshot::Sender<Result<String>>),
+    Shutdown(oneshot::Sender<()>),
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Identifies a BSS from the scan results of wpa_supplicant
+pub enum BssSelector {
+    Index(usize),
+    Bssid(Bssid),
+}
+
+impl fmt::Display for BssSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BssSelector::Index(index) => write!(f, "{index}"),
+            BssSelector::Bssid(bssid) => write!(f, "{bssid}"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+/// Optional arguments for the `SCAN` command
+pub(crate) struct ScanParams {
+    /// SSIDs to send directed probe requests for, finding hidden networks
+    pub(crate) ssids: Vec<String>,
+    /// Frequencies in MHz to restrict the scan to, all channels are scanned when empty
+    pub(crate) freqs: Vec<u32>,
+    /// Drop the cached BSS entries first, so that only networks seen by this scan remain
+    pub(crate) flush: bool,
+}
+
+impl ScanParams {
+    pub(crate) fn is_default(&self) -> bool {
+        self.ssids.is_empty() && self.freqs.is_empty() && !self.flush
+    }
+
+    pub(crate) fn command(&self) -> String {
+        let mut cmd = "SCAN".to_string();
+        for ssid in &self.ssids {
+            // hex encoding sidesteps quoting of SSIDs containing spaces or quotes
+            cmd.push_str(&format!(" ssid {}", hex_encode(ssid)));
+        }
+        if !self.freqs.is_empty() {
+            let freqs: Vec<String> = self.freqs.iter().map(u32::to_string).collect();
+            cmd.push_str(&format!(" freq={}", freqs.join(",")));
+        }
+        cmd
+    }
 }
 
 #[derive(Debug)]
 pub(crate) enum SetNetwork {
     Ssid(String),
     Psk(String),
+    Raw { key: String, value: String },
+}
+
+impl SetNetwork {
+    pub(crate) fn raw(key: &str, value: &str, quoting: Quoting) -> SetNetwork {
+        let value = match quoting {
+            Quoting::Quoted => format!("\"{value}\""),
+            Quoting::Unquoted => value.to_string(),
+        };
+        SetNetwork::Raw {
+            key: key.to_string(),
+            value,
+        }
+    }
+
+    pub(crate) fn command(&self, id: usize) -> String {
+        let param = match self {
+            SetNetwork::Ssid(ssid) if ssid_needs_hex(ssid) => format!("ssid {}", hex_encode(ssid)),
+            SetNetwork::Ssid(ssid) => format!("ssid \"{ssid}\""),
+            SetNetwork::Psk(psk) if is_raw_psk(psk) => format!("psk {psk}"),
+            SetNetwork::Psk(psk) => format!("psk \"{psk}\""),
+            SetNetwork::Raw { key, value } => format!("{key} {value}"),
+        };
+        format!("SET_NETWORK {id} {param}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Whether a raw network value is sent as a quoted string (eg: `ssid`, `identity`)
+/// or verbatim (eg: `priority`, `key_mgmt`, hex encoded values)
+pub enum Quoting {
+    Quoted,
+    Unquoted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// MAC address policy, mapping to the `mac_addr` and `preassoc_mac_addr` values of wpa_supplicant
+pub enum MacRandPolicy {
+    /// Use the permanent hardware address
+    Hardware,
+    /// Use a new random address for each connection
+    Random,
+    /// Like `Random`, but keep the vendor OUI of the hardware address
+    RandomKeepOui,
+}
+
+impl MacRandPolicy {
+    fn value(&self) -> &'static str {
+        match self {
+            MacRandPolicy::Hardware => "0",
+            MacRandPolicy::Random => "1",
+            MacRandPolicy::RandomKeepOui => "2",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Who scans for and selects access points, the global `ap_scan` setting of wpa_supplicant
+pub enum ApScanMode {
+    /// The driver scans and selects the access point itself, wpa_supplicant only
+    /// handles authentication. Used by some wired (IEEE 802.1X) and legacy drivers.
+    Driver,
+    /// wpa_supplicant scans and selects the access point, the default and the only mode
+    /// working with nl80211 drivers
+    Supplicant,
+    /// Like `Driver`, but networks are tried in the configured order by SSID and security
+    /// policy, without scanning. Needed by some drivers with hidden networks.
+    DriverOrdered,
+}
+
+impl ApScanMode {
+    fn value(&self) -> &'static str {
+        match self {
+            ApScanMode::Driver => "0",
+            ApScanMode::Supplicant => "1",
+            ApScanMode::DriverOrdered => "2",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Background scan module used for roaming between access points of a network.
+/// Intervals are in seconds and signal thresholds in dBm.
+pub enum BgscanConfig {
+    /// Scans every `short_interval` while the signal is below `signal_threshold`,
+    /// every `long_interval` otherwise
+    Simple {
+        short_interval: u32,
+        signal_threshold: i32,
+        long_interval: u32,
+    },
+    /// Like `Simple`, learning the channels of the network to speed up scans. The
+    /// learned data is kept in `database` when given.
+    Learn {
+        short_interval: u32,
+        signal_threshold: i32,
+        long_interval: u32,
+        database: Option<String>,
+    },
+}
+
+impl BgscanConfig {
+    fn signal_threshold(&self) -> i32 {
+        match self {
+            BgscanConfig::Simple {
+                signal_threshold, ..
+            }
+            | BgscanConfig::Learn {
+                signal_threshold, ..
+            } => *signal_threshold,
+        }
+    }
+}
+
+impl fmt::Display for BgscanConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BgscanConfig::Simple {
+                short_interval,
+                signal_threshold,
+                long_interval,
+            } => write!(
+                f,
+                "simple:{short_interval}:{signal_threshold}:{long_interval}"
+            ),
+            BgscanConfig::Learn {
+                short_interval,
+                signal_threshold,
+                long_interval,
+                database,
+            } => {
+                write!(
+                    f,
+                    "learn:{short_interval}:{signal_threshold}:{long_interval}"
+                )?;
+                if let Some(database) = database {
+                    write!(f, ":{database}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Scanning schedule wpa_supplicant follows while disconnected. Intervals are in seconds.
+pub enum AutoscanConfig {
+    /// Scans every `interval`
+    Periodic { interval: u32 },
+    /// Multiplies the interval by `base` after every scan, up to `limit`
+    Exponential { base: u32, limit: u32 },
+}
+
+impl AutoscanConfig {
+    fn is_valid(&self) -> bool {
+        match *self {
+            AutoscanConfig::Periodic { interval } => interval > 0,
+            AutoscanConfig::Exponential { base, limit } => base > 1 && limit >= base,
+        }
+    }
+}
+
+impl fmt::Display for AutoscanConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AutoscanConfig::Periodic { interval } => write!(f, "periodic:{interval}"),
+            AutoscanConfig::Exponential { base, limit } => {
+                write!(f, "exponential:{base}:{limit}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Static WEP keys of a legacy network. Keys are given as 5, 13 or 16 ASCII characters,
+/// or as 10, 26 or 32 hex digits.
+pub struct WepKeys {
+    pub keys: [Option<String>; 4],
+    /// Index of the key used for transmitting, which must be set
+    pub tx_key_index: usize,
+}
+
+impl WepKeys {
+    /// Whether the key is hex rather than ASCII, or `None` if it has an invalid length
+    fn is_hex(key: &str) -> Option<bool> {
+        match key.len() {
+            5 | 13 | 16 => Some(false),
+            10 | 26 | 32 if key.bytes().all(|b| b.is_ascii_hexdigit()) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// TLS versions which may be excluded from the negotiation with `Phase1::disable_tls`
+pub enum TlsVersion {
+    Tls1_0,
+    Tls1_1,
+    Tls1_2,
+    Tls1_3,
+}
+
+impl fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TlsVersion::Tls1_0 => write!(f, "1_0"),
+            TlsVersion::Tls1_1 => write!(f, "1_1"),
+            TlsVersion::Tls1_2 => write!(f, "1_2"),
+            TlsVersion::Tls1_3 => write!(f, "1_3"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Outer authentication options, the `phase1` network variable. The defaults leave the
+/// variable unset.
+pub struct Phase1 {
+    /// PEAP version to negotiate, 0 or 1
+    pub peap_version: Option<u8>,
+    /// Key derivation label for PEAPv1, 1 for servers using the newer label
+    pub peap_label: Option<u8>,
+    /// EAP-FAST PAC provisioning mode, 0 to 3
+    pub fast_provisioning: Option<u8>,
+    pub disable_tls: Vec<TlsVersion>,
+    /// Disables TLS session resumption with session tickets, which some servers mishandle
+    pub disable_session_ticket: bool,
+    /// Other options passed verbatim, eg: `tls_suiteb=1`
+    pub extra: Vec<String>,
+}
+
+impl Phase1 {
+    fn is_empty(&self) -> bool {
+        *self == Phase1::default()
+    }
+}
+
+impl fmt::Display for Phase1 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let numbers = [
+            ("peapver", self.peap_version),
+            ("peaplabel", self.peap_label),
+            ("fast_provisioning", self.fast_provisioning),
+        ];
+        let mut options: Vec<String> = numbers
+            .into_iter()
+            .filter_map(|(key, value)| Some(format!("{key}={}", value?)))
+            .collect();
+        for version in &self.disable_tls {
+            options.push(format!("tls_disable_tlsv{version}=1"));
+        }
+        if self.disable_session_ticket {
+            options.push("tls_disable_session_ticket=1".into());
+        }
+        options.extend(self.extra.iter().cloned());
+        write!(f, "{}", options.join(" "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Inner authentication method of a tunneled EAP method
+pub enum Phase2Auth {
+    Mschapv2,
+    Gtc,
+    Md5,
+    /// Non-EAP method, only tunneled by TTLS
+    Pap,
+    /// Non-EAP method, only tunneled by TTLS
+    Chap,
+    /// Non-EAP method, only tunneled by TTLS
+    Mschap,
+}
+
+impl Phase2Auth {
+    fn is_eap(&self) -> bool {
+        matches!(
+            self,
+            Phase2Auth::Mschapv2 | Phase2Auth::Gtc | Phase2Auth::Md5
+        )
+    }
+}
+
+impl fmt::Display for Phase2Auth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase2Auth::Mschapv2 => write!(f, "MSCHAPV2"),
+            Phase2Auth::Gtc => write!(f, "GTC"),
+            Phase2Auth::Md5 => write!(f, "MD5"),
+            Phase2Auth::Pap => write!(f, "PAP"),
+            Phase2Auth::Chap => write!(f, "CHAP"),
+            Phase2Auth::Mschap => write!(f, "MSCHAP"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Inner authentication, the `phase2` network variable
+pub enum Phase2 {
+    /// `auth=<method>`, eg: MSCHAPV2 inside PEAP or PAP inside TTLS
+    Auth(Phase2Auth),
+    /// `autheap=<method>`, an EAP method tunneled by TTLS
+    AuthEap(Phase2Auth),
+}
+
+impl fmt::Display for Phase2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase2::Auth(auth) => write!(f, "auth={auth}"),
+            Phase2::AuthEap(auth) => write!(f, "autheap={auth}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Explicit security settings of a network, for troubleshooting access points that don't
+/// negotiate well, eg: old ones needing `proto: vec!["WPA".into()]` with TKIP. Values are
+/// wpa_supplicant tokens and empty lists leave the setting as it is.
+pub struct SecurityOverrides {
+    /// `WPA` or `RSN`
+    pub proto: Vec<String>,
+    /// `CCMP`, `CCMP-256`, `GCMP`, `GCMP-256`, `TKIP` or `NONE`
+    pub pairwise: Vec<String>,
+    /// The pairwise ciphers or `WEP104` and `WEP40`
+    pub group: Vec<String>,
+    /// `OPEN`, `SHARED` or `LEAP`
+    pub auth_alg: Vec<String>,
+}
+
+impl SecurityOverrides {
+    const PROTO: [&'static str; 2] = ["WPA", "RSN"];
+    const PAIRWISE: [&'static str; 6] = ["CCMP", "CCMP-256", "GCMP", "GCMP-256", "TKIP", "NONE"];
+    const GROUP: [&'static str; 7] = [
+        "CCMP", "CCMP-256", "GCMP", "GCMP-256", "TKIP", "WEP104", "WEP40",
+    ];
+    const AUTH_ALG: [&'static str; 3] = ["OPEN", "SHARED", "LEAP"];
+    /// Ciphers that only RSN (WPA2) defines
+    const RSN_ONLY: [&'static str; 3] = ["CCMP-256", "GCMP", "GCMP-256"];
+
+    /// The settings to apply, rejecting unknown tokens and combinations wpa_supplicant
+    /// would accept but never manage to associate with
+    fn params(&self) -> Result<Vec<(&'static str, String)>> {
+        let settings = [
+            ("proto", &self.proto, &Self::PROTO[..]),
+            ("pairwise", &self.pairwise, &Self::PAIRWISE[..]),
+            ("group", &self.group, &Self::GROUP[..]),
+            ("auth_alg", &self.auth_alg, &Self::AUTH_ALG[..]),
+        ];
+        let mut params = Vec::new();
+        for (key, values, known) in settings {
+            if let Some(value) = values.iter().find(|value| !known.contains(&value.as_str())) {
+                return Err(error::Error::InvalidSecurityOverride {
+                    key,
+                    value: value.clone(),
+                });
+            }
+            if !values.is_empty() {
+                params.push((key, values.join(" ")));
+            }
+        }
+        let has = |values: &[String], tokens: &[&str]| {
+            values.iter().any(|value| tokens.contains(&value.as_str()))
+        };
+        let only = |values: &[String], token: &str| {
+            !values.is_empty() && values.iter().all(|value| value == token)
+        };
+        let conflict = if has(&self.pairwise, &["NONE"]) && self.pairwise.len() > 1 {
+            Some("pairwise NONE with other ciphers")
+        } else if only(&self.proto, "WPA")
+            && (has(&self.pairwise, &Self::RSN_ONLY) || has(&self.group, &Self::RSN_ONLY))
+        {
+            Some("proto WPA with ciphers only RSN defines")
+        } else if only(&self.pairwise, "TKIP")
+            && !self.group.is_empty()
+            && !has(&self.group, &["TKIP", "WEP104", "WEP40"])
+        {
+            // the group cipher may not be stronger than the pairwise one
+            Some("pairwise TKIP with a stronger group cipher")
+        } else if has(&self.auth_alg, &["SHARED"])
+            && !(self.proto.is_empty() && self.pairwise.is_empty())
+        {
+            Some("auth_alg SHARED, which is WEP only, with WPA settings")
+        } else {
+            None
+        };
+        if let Some(conflict) = conflict {
+            return Err(error::Error::ConflictingSecurityOverrides(conflict));
+        }
+        Ok(params)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// WPA-Enterprise (802.1X) settings for a network. Certificates and keys are given either
+/// as files or, for keys kept on smartcards or TPMs, as ids resolved by an OpenSSL engine.
+pub struct EapConfig {
+    /// EAP method, eg: `PEAP`, `TTLS` or `TLS`
+    pub method: String,
+    pub identity: String,
+    pub password: Option<String>,
+    /// Path to the CA certificate used to validate the authentication server
+    pub ca_cert: Option<String>,
+    /// Path to the client certificate for TLS client authentication
+    pub client_cert: Option<String>,
+    /// Path to the private key of the client certificate
+    pub private_key: Option<String>,
+    pub phase1: Phase1,
+    /// Inner authentication, only used by the PEAP, TTLS and FAST methods
+    pub phase2: Option<Phase2>,
+    /// Path to the EAP-FAST PAC file, eg: `blob://eap-fast-pac` to keep it in memory
+    pub pac_file: Option<String>,
+    /// Loads the credentials below through the OpenSSL engine `engine_id`
+    pub engine: bool,
+    /// OpenSSL engine, eg: `pkcs11`
+    pub engine_id: Option<String>,
+    /// Engine id of the private key, instead of `private_key`
+    pub key_id: Option<String>,
+    /// Engine id of the client certificate, instead of `client_cert`
+    pub cert_id: Option<String>,
+    /// Engine id of the CA certificate, instead of `ca_cert`
+    pub ca_cert_id: Option<String>,
+}
+
+impl EapConfig {
+    /// Rejects the options the method doesn't use, which wpa_supplicant silently ignores
+    fn validate_options(&self) -> Result {
+        let method = self.method.to_ascii_uppercase();
+        let unused = |option: String| {
+            Err(error::Error::UnusedEapOption {
+                method: self.method.clone(),
+                option,
+            })
+        };
+        let phase1 = &self.phase1;
+        if method != "PEAP" && (phase1.peap_version.is_some() || phase1.peap_label.is_some()) {
+            return unused("phase1 peap options".into());
+        }
+        if method != "FAST" && phase1.fast_provisioning.is_some() {
+            return unused("phase1 fast_provisioning".into());
+        }
+        if method != "FAST" && self.pac_file.is_some() {
+            return unused("pac_file".into());
+        }
+        let invalid = [
+            matches!(phase1.peap_version, Some(version) if version > 1),
+            matches!(phase1.peap_label, Some(label) if label > 1),
+            matches!(phase1.fast_provisioning, Some(mode) if mode > 3),
+        ];
+        if invalid.contains(&true) {
+            return Err(error::Error::InvalidEapPhase1(phase1.to_string()));
+        }
+        match (method.as_str(), self.phase2) {
+            (_, None) | ("TTLS", Some(Phase2::Auth(_))) => Ok(()),
+            ("TTLS", Some(Phase2::AuthEap(auth))) | ("PEAP" | "FAST", Some(Phase2::Auth(auth)))
+                if auth.is_eap() =>
+            {
+                Ok(())
+            }
+            (_, Some(phase2)) => unused(phase2.to_string()),
+        }
+    }
+
+    fn params(&self) -> Result<Vec<SetNetwork>> {
+        let credentials = [
+            ("ca_cert", &self.ca_cert, &self.ca_cert_id),
+            ("client_cert", &self.client_cert, &self.cert_id),
+            ("private_key", &self.private_key, &self.key_id),
+        ];
+        for (credential, file, id) in credentials {
+            if file.is_some() && id.is_some() {
+                return Err(error::Error::ConflictingEapCredential(credential));
+            }
+        }
+        self.validate_options()?;
+        let mut params = vec![
+            SetNetwork::raw("key_mgmt", "WPA-EAP", Quoting::Unquoted),
+            SetNetwork::raw("eap", &self.method, Quoting::Unquoted),
+            SetNetwork::raw("identity", &self.identity, Quoting::Quoted),
+        ];
+        let phase1 = (!self.phase1.is_empty()).then(|| self.phase1.to_string());
+        let phase2 = self.phase2.map(|phase2| phase2.to_string());
+        let optional = [
+            ("password", &self.password),
+            ("ca_cert", &self.ca_cert),
+            ("client_cert", &self.client_cert),
+            ("private_key", &self.private_key),
+            ("phase1", &phase1),
+            ("phase2", &phase2),
+            ("pac_file", &self.pac_file),
+            ("engine_id", &self.engine_id),
+            ("key_id", &self.key_id),
+            ("cert_id", &self.cert_id),
+            ("ca_cert_id", &self.ca_cert_id),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                params.push(SetNetwork::raw(key, value, Quoting::Quoted));
+            }
+        }
+        if self.engine {
+            params.push(SetNetwork::raw("engine", "1", Quoting::Unquoted));
+        }
+        Ok(params)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// How a network configured with `NetworkSpec` authenticates
+pub enum NetworkSecurity {
+    Open,
+    /// WPA2-Personal with a passphrase or a raw key
+    Psk(String),
+    /// WPA3-Personal, see `set_network_sae`
+    Sae(String),
+    /// WPA-Enterprise
+    Eap(Box<EapConfig>),
+}
+
+#[derive(Debug, Clone)]
+/// Complete configuration of a network, applied by `RequestClient::add_and_configure`
+pub struct NetworkSpec {
+    pub ssid: String,
+    pub security: NetworkSecurity,
+    pub priority: Option<i32>,
+    /// Probe for the SSID when scanning, needed for hidden networks
+    pub scan_ssid: bool,
+    /// Only connect to this access point of the network
+    pub bssid: Option<Bssid>,
+    /// Issues `DISABLE_NETWORK` right after `ADD_NETWORK`, before anything is
+    /// configured, so that wpa_supplicant won't try the network until it is enabled with
+    /// `enable_network`, eg: when pre-provisioning many networks
+    pub disabled: bool,
+}
+
+impl NetworkSpec {
+    /// Creates a spec for the SSID with default settings
+    pub fn new(ssid: &str, security: NetworkSecurity) -> NetworkSpec {
+        NetworkSpec {
+            ssid: ssid.to_string(),
+            security,
+            priority: None,
+            scan_ssid: false,
+            bssid: None,
+            disabled: false,
+        }
+    }
+
+    fn params(self) -> Result<Vec<SetNetwork>> {
+        validate_ssid(&self.ssid)?;
+        let mut params = vec![SetNetwork::Ssid(self.ssid)];
+        match self.security {
+            NetworkSecurity::Open => {
+                params.push(SetNetwork::raw("key_mgmt", "NONE", Quoting::Unquoted))
+            }
+            NetworkSecurity::Psk(psk) => {
+                validate_psk(&psk)?;
+                params.push(SetNetwork::Psk(psk));
+            }
+            NetworkSecurity::Sae(passphrase) => {
+                validate_psk(&passphrase)?;
+                if is_raw_psk(&passphrase) {
+                    return Err(error::Error::InvalidPsk);
+                }
+                params.push(SetNetwork::raw("key_mgmt", "SAE", Quoting::Unquoted));
+                params.push(SetNetwork::raw("ieee80211w", "2", Quoting::Unquoted));
+                params.push(SetNetwork::Psk(passphrase));
+            }
+            NetworkSecurity::Eap(cfg) => params.extend(cfg.params()?),
+        }
+        if let Some(priority) = self.priority {
+            let priority = priority.to_string();
+            params.push(SetNetwork::raw("priority", &priority, Quoting::Unquoted));
+        }
+        if self.scan_ssid {
+            params.push(SetNetwork::raw("scan_ssid", "1", Quoting::Unquoted));
+        }
+        if let Some(bssid) = self.bssid {
+            let bssid = bssid.to_string();
+            params.push(SetNetwork::raw("bssid", &bssid, Quoting::Unquoted));
+        }
+        Ok(params)
+    }
 }
 
 #[derive(Clone)]
-/// Request client wraps the request events, awaiting oneshot channels when appropriate
+/// Request client wraps the request events, awaiting oneshot channels when appropriate.
+///
+/// The runtime handles requests one at a time in the order they are sent, so the calls of
+/// a task are never reordered. Clones share the runtime though, and the requests of several
+/// tasks may interleave. Use `transaction` for multi-step operations that must not be
+/// interleaved, eg: reconfiguring and selecting a network.
 pub struct RequestClient {
-    sender: mpsc::Sender<Request>,
+    pub(crate) sender: mpsc::Sender<Request>,
+    /// How long to wait for the runtime to answer a request
+    timeout: tokio::time::Duration,
+    /// Set by the runtime while the control socket is lost
+    disconnected: Arc<AtomicBool>,
+    /// Name of the interface controlled by the runtime, unknown on the global socket
+    pub(crate) interface: Option<String>,
+    /// Held by a transaction, and briefly by every request sent outside of it
+    transaction_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Whether this client is the one handed to a transaction, which already holds the lock
+    in_transaction: bool,
+    /// Lets `wait_for` subscribe without keeping the broadcast channel open
+    broadcast_sender: broadcast::WeakSender<Broadcast>,
+    /// Shared by the clones so that capabilities are only queried once
+    capabilities: Arc<tokio::sync::OnceCell<Capabilities>>,
 }
 
 impl RequestClient {
-    pub(crate) fn new(sender: mpsc::Sender<Request>) -> RequestClient {
-        RequestClient { sender }
+    pub(crate) fn new(
+        sender: mpsc::Sender<Request>,
+        disconnected: Arc<AtomicBool>,
+        interface: Option<String>,
+        broadcast_sender: broadcast::WeakSender<Broadcast>,
+    ) -> RequestClient {
+        RequestClient {
+            sender,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            disconnected,
+            interface,
+            transaction_lock: Arc::new(tokio::sync::Mutex::new(())),
+            in_transaction: false,
+            broadcast_sender,
+            capabilities: Arc::default(),
+        }
+    }
+
+    /// Name of the interface this client controls, eg: `wlan0`, taken from the control
+    /// socket. `None` on the global control socket, which serves every interface.
+    pub fn interface(&self) -> Option<&str> {
+        self.interface.as_deref()
     }
 
-    async fn send_request(&self, request: Request) -> Result {
+    /// Returns a client that gives up on responses after `timeout`, returning
+    /// `Error::RequestTimeout`. Defaults to 10 seconds.
+    pub fn with_timeout(mut self, timeout: tokio::time::Duration) -> RequestClient {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Waits for the first broadcast matching `predicate`, failing with `Error::Timeout`
+    /// once `timeout` elapses. Events are collected from the time of the call rather than
+    /// from the first poll, so the wait may be set up before triggering what it waits for:
+    /// `let connected = client.wait_for(..); client.reconnect().await?; connected.await`.
+    /// A lagging subscription skips the missed events.
+    pub fn wait_for<P>(
+        &self,
+        predicate: P,
+        timeout: tokio::time::Duration,
+    ) -> impl std::future::Future<Output = Result<Broadcast>>
+    where
+        P: Fn(&Broadcast) -> bool,
+    {
+        let receiver = self
+            .broadcast_sender
+            .upgrade()
+            .map(|sender| sender.subscribe());
+        async move {
+            let mut receiver = receiver.ok_or(error::Error::WifiStationEventChannelClosed)?;
+            let wait = async {
+                loop {
+                    match receiver.recv().await {
+                        Ok(broadcast) if predicate(&broadcast) => return Ok(broadcast),
+                        Ok(_) => (),
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            warn!("Broadcast receiver lagged, skipped {n} events");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {
+                            return Err(error::Error::WifiStationEventChannelClosed)
+                        }
+                    }
+                }
+            };
+            tokio::time::timeout(timeout, wait)
+                .await
+                .map_err(|_| error::Error::Timeout)?
+        }
+    }
+
+    /// Runs `f` with a client whose requests are not interleaved with those of any other
+    /// clone. Other clones wait until the transaction completes before sending requests,
+    /// so `f` must only use the client it is given or it deadlocks. Transactions started
+    /// from within `f` simply join the running one.
+    pub async fn transaction<F, Fut, T>(&self, f: F) -> T
+    where
+        F: FnOnce(RequestClient) -> Fut,
+        Fut: std::future::Future<Output = T>,
+    {
+        if self.in_transaction {
+            return f(self.clone()).await;
+        }
+        let _guard = self.transaction_lock.lock().await;
+        let mut tx = self.clone();
+        tx.in_transaction = true;
+        f(tx).await
+    }
+
+    pub(crate) async fn send_request(&self, request: Request) -> Result {
+        if self.disconnected.load(Ordering::Relaxed) {
+            return Err(error::Error::ControlSocketDisconnected);
+        }
+        let _guard = match self.in_transaction {
+            true => None,
+            false => Some(self.transaction_lock.lock().await),
+        };
         self.sender
             .send(request)
             .await
@@ -65,45 +909,439 @@ impl RequestClient {
         Ok(())
     }
 
+    pub(crate) async fn await_response<T>(&self, request: oneshot::Receiver<T>) -> Result<T> {
+        tokio::time::timeout(self.timeout, request)
+            .await
+            .map_err(|_| error::Error::RequestTimeout)?
+            .map_err(|e| {
+                // the runtime drops pending requests when it loses the control socket
+                if self.disconnected.load(Ordering::Relaxed) {
+                    error::Error::ControlSocketDisconnected
+                } else {
+                    error::Error::from(e)
+                }
+            })
+    }
+
+    /// Sends a control command as is, returning the reply text
+    pub(crate) async fn raw(&self, cmd: impl Into<String>) -> Result<String> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Raw(cmd.into(), response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Sends a control command expected to reply `OK`
+    pub(crate) async fn expect_ok(&self, cmd: impl Into<String>) -> Result {
+        match self.raw(cmd).await? {
+            resp if resp == "OK" => Ok(()),
+            resp => Err(error::Error::UnexpectedWifiApRepsonse(resp)),
+        }
+    }
+
+    /// Sends a control command replying with data, returning `FAIL` and commands this
+    /// wpa_supplicant doesn't know as `Error::UnexpectedWifiApRepsonse`
+    pub(crate) async fn raw_checked(&self, cmd: impl Into<String>) -> Result<String> {
+        match self.raw(cmd).await? {
+            resp if resp.starts_with("FAIL") || resp == "UNKNOWN COMMAND" => {
+                Err(error::Error::UnexpectedWifiApRepsonse(resp))
+            }
+            resp => Ok(resp),
+        }
+    }
+
+    /// Scans for networks. Scans may be cancelled by dropping the future, in which case
+    /// wpa_supplicant is told to abort the scan unless other requests await it.
     pub async fn get_scan(&self) -> Result<Arc<Vec<ScanResult>>> {
+        self.get_scan_outcome().await?.complete_results()
+    }
+
+    /// Like `get_scan`, but a failed scan still returns the results wpa_supplicant has
+    /// cached, marked as incomplete so callers can decide whether to trust them or rescan
+    pub async fn get_scan_outcome(&self) -> Result<ScanOutcome> {
+        self.scan(ScanParams::default()).await
+    }
+
+    /// Like `get_scan`, but flushes the BSS entries cached by wpa_supplicant first, so that
+    /// networks which have vanished since earlier scans aren't returned. See `flush_bss`
+    /// for the effect on roaming.
+    pub async fn get_scan_fresh(&self) -> Result<Arc<Vec<ScanResult>>> {
+        let params = ScanParams {
+            flush: true,
+            ..Default::default()
+        };
+        self.scan(params).await?.complete_results()
+    }
+
+    async fn scan(&self, params: ScanParams) -> Result<ScanOutcome> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Scan(params, response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// Scans for networks, keeping only the strongest BSS of each SSID. See `get_scan` for
+    /// every BSS.
+    pub async fn get_scan_grouped(&self) -> Result<Vec<ScanGroup>> {
+        let results = self.get_scan().await?;
+        Ok(ScanGroup::group(&results))
+    }
+
+    /// Scans while actively probing for the given SSIDs, so that hidden networks are
+    /// included in the results. The SSIDs are only used for this scan and are not saved.
+    /// SSIDs with embedded spaces or quotes may be passed verbatim.
+    pub async fn get_scan_hidden(&self, ssids: Vec<String>) -> Result<Arc<Vec<ScanResult>>> {
+        let params = ScanParams {
+            ssids,
+            ..Default::default()
+        };
+        self.scan(params).await?.complete_results()
+    }
+
+    /// Scans only the given frequencies (in MHz), which is much faster than a full scan
+    /// when the channel of the network is known. An empty slice scans all channels.
+    /// Frequencies outside 2.4 GHz to 7.125 GHz are rejected as `Error::InvalidFrequency`,
+    /// anything within is left for the driver to judge, eg: 4.9 GHz public safety channels.
+    pub async fn get_scan_freqs(&self, freqs: &[u32]) -> Result<Arc<Vec<ScanResult>>> {
+        if let Some(freq) = freqs.iter().find(|f| !(2400..=7125).contains(*f)) {
+            return Err(error::Error::InvalidFrequency(*freq));
+        }
+        let params = ScanParams {
+            freqs: freqs.to_vec(),
+            ..Default::default()
+        };
+        self.scan(params).await?.complete_results()
+    }
+
+    /// Whether a scan is running, whether requested through this crate or started by
+    /// wpa_supplicant itself. Scanning while one is in flight waits for its results, see
+    /// `WifiSetup::coalesce_scans`, or fails with `Error::ScanBusy` for scans restricted to
+    /// SSIDs or frequencies.
+    pub async fn scan_in_progress(&self) -> Result<bool> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::ScanInProgress(response)).await?;
+        self.await_response(request).await
+    }
+
+    /// Queries the full details of one BSS known to wpa_supplicant, including its
+    /// information elements. Returns `None` if there is no such BSS.
+    pub async fn get_bss(&self, selector: BssSelector) -> Result<Option<BssInfo>> {
         let (response, request) = oneshot::channel();
-        self.send_request(Request::Scan(response)).await?;
-        Ok(request.await?)
+        self.send_request(Request::Bss(selector, response)).await?;
+        self.await_response(request).await?
     }
 
     pub async fn get_networks(&self) -> Result<Vec<NetworkResult>> {
         let (response, request) = oneshot::channel();
         self.send_request(Request::Networks(response)).await?;
-        Ok(request.await?)
+        self.await_response(request).await?
     }
 
     pub async fn get_status(&self) -> Result<Result<Status>> {
         let (response, request) = oneshot::channel();
         self.send_request(Request::Status(response)).await?;
-        Ok(request.await?)
+        self.await_response(request).await
+    }
+
+    /// Polls the live signal strength and link speed of the current connection
+    pub async fn signal_poll(&self) -> Result<SignalPoll> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::SignalPoll(response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// When the station connected to the current network, or `None` while disconnected.
+    /// Roaming between access points of the network keeps the original time. The time is
+    /// also unknown until the next connection after the runtime (re)attaches to the socket.
+    pub async fn connected_since(&self) -> Result<Option<std::time::Instant>> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::ConnectedSince(response)).await?;
+        self.await_response(request).await
+    }
+
+    /// How long the station has been connected to the current network, see `connected_since`
+    pub async fn uptime(&self) -> Result<Option<tokio::time::Duration>> {
+        Ok(self.connected_since().await?.map(|since| since.elapsed()))
+    }
+
+    /// Version of the wpa_supplicant answering, read with `GET version`, which very old
+    /// releases don't support
+    pub async fn version(&self) -> Result<SupplicantVersion> {
+        let version = self.get("version").await?;
+        SupplicantVersion::from_response(&version)
+    }
+
+    /// Reads the MIB variables, useful for debugging enterprise authentication failures
+    pub async fn get_mib(&self) -> Result<Mib> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Mib(response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// Reads the traffic counters of the current link. Byte counts are read from sysfs,
+    /// so this fails with `Error::UnknownInterface` on the global control socket.
+    pub async fn get_link_stats(&self) -> Result<LinkStats> {
+        let interface = self
+            .interface()
+            .ok_or(error::Error::UnknownInterface)?
+            .to_string();
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::PktcntPoll(response)).await?;
+        let mut stats = self.await_response(request).await??;
+        // wpa_supplicant doesn't count bytes, but the kernel does
+        stats.rx_bytes = Self::interface_statistic(&interface, "rx_bytes").await;
+        stats.tx_bytes = Self::interface_statistic(&interface, "tx_bytes").await;
+        Ok(stats)
+    }
+
+    /// Reads a counter of the interface from sysfs without blocking the runtime, `None`
+    /// when the kernel doesn't report it
+    async fn interface_statistic(interface: &str, name: &str) -> Option<u64> {
+        let path = format!("/sys/class/net/{interface}/statistics/{name}");
+        tokio::fs::read_to_string(path)
+            .await
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
     }
 
     pub async fn add_network(&self) -> Result<usize> {
         let (response, request) = oneshot::channel();
         self.send_request(Request::AddNetwork(response)).await?;
-        Ok(request.await?)
+        self.await_response(request).await
     }
 
+    /// Adds a network and applies the whole spec in a single request to the runtime,
+    /// returning the new network id. If any setting fails the network is removed, so
+    /// no half-configured network is left behind.
+    pub async fn add_and_configure(&self, spec: NetworkSpec) -> Result<usize> {
+        let disabled = spec.disabled;
+        let params = spec.params()?;
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::AddConfiguredNetwork(params, disabled, response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Adds and configures a network like `add_and_configure`, then selects it, which
+    /// disables every other network. Returns the network id along with the outcome of the
+    /// selection, leaving the network in place whatever the outcome so the caller can
+    /// retry or remove it. Should the selection fail with an error, or the future be
+    /// dropped, the network is removed again.
+    pub async fn add_and_select(&self, spec: NetworkSpec) -> Result<(usize, SelectResult)> {
+        let network_id = self.add_and_configure(spec).await?;
+        let mut guard = NetworkGuard {
+            client: self,
+            network_id: Some(network_id),
+        };
+        let result = self.select_network(network_id).await;
+        guard.network_id = None;
+        match result {
+            Ok(result) => Ok((network_id, result)),
+            Err(e) => {
+                self.remove_network(network_id).await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn set_network(&self, network_id: usize, param: SetNetwork) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::SetNetwork(network_id, param, response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Sets the passphrase, 8 to 63 printable ASCII characters, or the raw key given as
+    /// 64 hex digits
     pub async fn set_network_psk(&self, network_id: usize, psk: String) -> Result {
-        self.send_request(Request::SetNetwork(network_id, SetNetwork::Psk(psk)))
+        validate_psk(&psk)?;
+        self.set_network(network_id, SetNetwork::Psk(psk)).await
+    }
+
+    /// Sets the SSID, 1 to 32 bytes. SSIDs with quotes or control characters are sent
+    /// hex encoded.
+    pub async fn set_network_ssid(&self, network_id: usize, ssid: String) -> Result {
+        validate_ssid(&ssid)?;
+        self.set_network(network_id, SetNetwork::Ssid(ssid)).await
+    }
+
+    /// Sets an SSID given as bytes, eg: `ScanResult::ssid_bytes` of a network whose SSID
+    /// isn't valid UTF-8. The SSID is always sent hex encoded.
+    pub async fn set_network_ssid_bytes(&self, network_id: usize, ssid: &[u8]) -> Result {
+        if ssid.is_empty() || ssid.len() > 32 {
+            return Err(error::Error::InvalidSsid(
+                String::from_utf8_lossy(ssid).into_owned(),
+            ));
+        }
+        self.set_network_raw(network_id, "ssid", &hex_encode(ssid), Quoting::Unquoted)
+            .await
+    }
+
+    /// Configures the network for WPA3-Personal, setting `key_mgmt=SAE` with management frame
+    /// protection required (`ieee80211w=2`) along with the passphrase.
+    pub async fn set_network_sae(&self, network_id: usize, passphrase: String) -> Result {
+        // checked before touching the network, and SAE derives its keys from the
+        // passphrase itself so raw keys are refused
+        validate_psk(&passphrase)?;
+        if is_raw_psk(&passphrase) {
+            return Err(error::Error::InvalidPsk);
+        }
+        self.set_network_raw(network_id, "key_mgmt", "SAE", Quoting::Unquoted)
+            .await?;
+        self.set_network_raw(network_id, "ieee80211w", "2", Quoting::Unquoted)
             .await?;
+        self.set_network(network_id, SetNetwork::Psk(passphrase))
+            .await
+    }
+
+    /// Configures the network for WPA-Enterprise. Each field is set individually, so an
+    /// error tells the caller the network may be partially configured.
+    pub async fn set_network_eap(&self, network_id: usize, cfg: EapConfig) -> Result {
+        for param in cfg.params()? {
+            self.set_network(network_id, param).await?;
+        }
         Ok(())
     }
 
-    pub async fn set_network_ssid(&self, network_id: usize, ssid: String) -> Result {
-        self.send_request(Request::SetNetwork(network_id, SetNetwork::Ssid(ssid)))
+    /// Sets the MAC address policy used when connecting to this network
+    pub async fn set_mac_randomization(&self, network_id: usize, policy: MacRandPolicy) -> Result {
+        self.set_network_raw(network_id, "mac_addr", policy.value(), Quoting::Unquoted)
+            .await
+    }
+
+    /// Sets the default MAC address policy for connections as well as the policy used while
+    /// scanning before association. Fails if the driver doesn't support randomization.
+    pub async fn set_global_mac_randomization(&self, policy: MacRandPolicy) -> Result {
+        self.set("mac_addr", policy.value()).await?;
+        self.set("preassoc_mac_addr", policy.value()).await
+    }
+
+    /// Configures the network for WEP. WEP is broken and offers no real protection, this
+    /// is only meant for legacy equipment that supports nothing else.
+    pub async fn set_network_wep(&self, network_id: usize, keys: WepKeys) -> Result {
+        if keys.keys.get(keys.tx_key_index).is_none_or(Option::is_none) {
+            return Err(error::Error::InvalidWepKeyIndex(keys.tx_key_index));
+        }
+        let mut quoting = [Quoting::Unquoted; 4];
+        for (index, key) in keys.keys.iter().enumerate() {
+            if let Some(key) = key {
+                quoting[index] = match WepKeys::is_hex(key) {
+                    Some(true) => Quoting::Unquoted,
+                    Some(false) => Quoting::Quoted,
+                    None => return Err(error::Error::InvalidWepKey(index)),
+                };
+            }
+        }
+        warn!("Configuring network {network_id} with insecure WEP");
+        self.set_network_raw(network_id, "key_mgmt", "NONE", Quoting::Unquoted)
             .await?;
+        for (index, key) in keys.keys.iter().enumerate() {
+            if let Some(key) = key {
+                let name = format!("wep_key{index}");
+                self.set_network_raw(network_id, &name, key, quoting[index])
+                    .await?;
+            }
+        }
+        let tx_key_index = keys.tx_key_index.to_string();
+        self.set_network_raw(
+            network_id,
+            "wep_tx_keyidx",
+            &tx_key_index,
+            Quoting::Unquoted,
+        )
+        .await
+    }
+
+    /// Overrides the negotiated security of the network, see `SecurityOverrides`. The
+    /// overrides are validated as a whole before any is applied.
+    pub async fn set_network_security(
+        &self,
+        network_id: usize,
+        overrides: SecurityOverrides,
+    ) -> Result {
+        for (key, value) in overrides.params()? {
+            self.set_network_raw(network_id, key, &value, Quoting::Unquoted)
+                .await?;
+        }
         Ok(())
     }
 
+    /// Configures background scanning for roaming, eg: `simple:30:-70:300`. The signal
+    /// threshold must be between -100 and 0 dBm.
+    pub async fn set_network_bgscan(&self, network_id: usize, cfg: BgscanConfig) -> Result {
+        let threshold = cfg.signal_threshold();
+        if !(-100..=0).contains(&threshold) {
+            return Err(error::Error::InvalidSignalThreshold(threshold));
+        }
+        self.set_network_raw(network_id, "bgscan", &cfg.to_string(), Quoting::Quoted)
+            .await
+    }
+
+    /// Sets the priority wpa_supplicant uses to choose among available networks.
+    /// Higher values are preferred and negative values are allowed.
+    pub async fn set_network_priority(&self, network_id: usize, priority: i32) -> Result {
+        self.set_network_raw(
+            network_id,
+            "priority",
+            &priority.to_string(),
+            Quoting::Unquoted,
+        )
+        .await
+    }
+
+    /// Enables active probing for the network's SSID, which is required to connect to a
+    /// network that doesn't broadcast its SSID
+    pub async fn set_network_scan_ssid(&self, network_id: usize, enabled: bool) -> Result {
+        let value = if enabled { "1" } else { "0" };
+        self.set_network_raw(network_id, "scan_ssid", value, Quoting::Unquoted)
+            .await
+    }
+
+    /// Sets any network variable, issuing `SET_NETWORK <id> <key> <value>`.
+    /// A `FAIL` reply from wpa_supplicant is returned as an error.
+    pub async fn set_network_raw(
+        &self,
+        network_id: usize,
+        key: &str,
+        value: &str,
+        quoting: Quoting,
+    ) -> Result {
+        self.set_network(network_id, SetNetwork::raw(key, value, quoting))
+            .await
+    }
+
+    /// Reads a network variable, issuing `GET_NETWORK <id> <key>`. The value is returned as
+    /// wpa_supplicant formats it, so strings such as `ssid` are quoted. Secrets such as `psk`
+    /// are never returned in plaintext: wpa_supplicant masks them or returns the derived key.
+    pub async fn get_network_var(&self, network_id: usize, key: &str) -> Result<String> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::GetNetwork(network_id, key.into(), response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Writes the current configuration back to the wpa_supplicant configuration file.
+    /// Fails with `Error::SaveConfigDisabled` unless `update_config` is enabled, see
+    /// `set_update_config`.
     pub async fn save_config(&self) -> Result {
-        self.send_request(Request::SaveConfig).await?;
-        Ok(())
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::SaveConfig(response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// Allows or forbids `save_config` to overwrite the configuration file
+    pub async fn set_update_config(&self, enabled: bool) -> Result {
+        self.set("update_config", if enabled { "1" } else { "0" })
+            .await
+    }
+
+    /// Makes wpa_supplicant reload its configuration file, eg: after it was edited elsewhere.
+    /// Unsaved changes made through this client are discarded, see `save_config`.
+    pub async fn reconfigure(&self) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Reconfigure(response)).await?;
+        self.await_response(request).await?
     }
 
     pub async fn remove_network(&self, network_id: usize) -> Result {
@@ -112,29 +1350,646 @@ impl RequestClient {
         Ok(())
     }
 
+    /// Removes every saved network with the given SSID, returning how many wpa_supplicant
+    /// confirmed removing. Finding none is not an error, and networks it refuses to
+    /// remove, eg: one removed in the meantime, are left out of the count.
+    pub async fn remove_network_by_ssid(&self, ssid: &str) -> Result<usize> {
+        let networks = self.get_networks().await?;
+        let mut removed = 0;
+        for network in networks.iter().filter(|network| network.ssid == ssid) {
+            let id = network.network_id;
+            match self.expect_ok(format!("REMOVE_NETWORK {id}")).await {
+                Ok(()) => removed += 1,
+                Err(error::Error::UnexpectedWifiApRepsonse(resp)) => {
+                    warn!("wpa_supplicant refused to remove network {id}: {resp}")
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Removes every configured network, returning how many there were. The saved
+    /// configuration is only updated by a subsequent `save_config`.
+    pub async fn remove_all_networks(&self) -> Result<usize> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::RemoveAllNetworks(response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Re-enables a saved network so wpa_supplicant may connect to it again
+    pub async fn enable_network(&self, network_id: usize) -> Result {
+        self.send_request(Request::EnableNetwork(network_id))
+            .await?;
+        Ok(())
+    }
+
+    /// Disables a saved network without removing its configuration
+    pub async fn disable_network(&self, network_id: usize) -> Result {
+        self.send_request(Request::DisableNetwork(network_id))
+            .await?;
+        Ok(())
+    }
+
     pub async fn select_network(&self, network_id: usize) -> Result<SelectResult> {
         let (response, request) = oneshot::channel();
         self.send_request(Request::SelectNetwork(network_id, response))
             .await?;
-        Ok(request.await?)
+        self.await_response(request).await
+    }
+
+    /// Adds a network for the SSID, open when no passphrase is given, and selects it,
+    /// waiting up to `timeout` for the outcome. The network is removed again unless the
+    /// connection succeeds, including when the future is dropped; on success it is left
+    /// in place but not saved.
+    pub async fn connect(
+        &self,
+        ssid: &str,
+        psk: Option<&str>,
+        timeout: tokio::time::Duration,
+    ) -> Result<ConnectOutcome> {
+        validate_ssid(ssid)?;
+        psk.map(validate_psk).transpose()?;
+        let network_id = self.add_network().await?;
+        let mut guard = NetworkGuard {
+            client: self,
+            network_id: Some(network_id),
+        };
+        let outcome = self.try_connect(network_id, ssid, psk, timeout).await;
+        guard.network_id = None;
+        if !matches!(outcome, Ok(ConnectOutcome::Connected)) {
+            // also ends the selection the runtime still awaits after a timeout
+            self.remove_network(network_id).await?;
+        }
+        outcome
+    }
+
+    /// Scans and connects to the strongest open network, falling back to the next
+    /// strongest when a connection fails, each attempt waiting up to `timeout`. Returns the
+    /// SSID connected to, or `None` once every open network failed.
+    ///
+    /// Whether an open network requires a captive portal login can't be told from a scan,
+    /// so the connection may succeed without giving internet access. Callers needing it
+    /// must probe for it once connected.
+    pub async fn connect_best_open(
+        &self,
+        timeout: tokio::time::Duration,
+    ) -> Result<Option<String>> {
+        let results = self.get_scan().await?;
+        let open: Vec<ScanResult> = results
+            .iter()
+            .filter(|result| result.is_open() && !result.name.is_empty())
+            .cloned()
+            .collect();
+        for group in ScanGroup::group(&open) {
+            // connect takes the SSID as text, so binary SSIDs are out of reach
+            let Ok(ssid) = String::from_utf8(group.strongest.ssid_bytes()) else {
+                continue;
+            };
+            match self.connect(&ssid, None, timeout).await? {
+                ConnectOutcome::Connected => return Ok(Some(ssid)),
+                outcome => warn!("Connecting to open network {ssid:?}: {outcome:?}"),
+            }
+        }
+        Ok(None)
+    }
+
+    async fn try_connect(
+        &self,
+        network_id: usize,
+        ssid: &str,
+        psk: Option<&str>,
+        timeout: tokio::time::Duration,
+    ) -> Result<ConnectOutcome> {
+        self.set_network_ssid(network_id, ssid.to_string()).await?;
+        match psk {
+            Some(psk) => self.set_network_psk(network_id, psk.to_string()).await?,
+            None => {
+                self.set_network_raw(network_id, "key_mgmt", "NONE", Quoting::Unquoted)
+                    .await?
+            }
+        }
+        let client = self.clone().with_timeout(timeout);
+        match client.select_network(network_id).await {
+            Ok(SelectResult::Success) => Ok(ConnectOutcome::Connected),
+            Ok(SelectResult::WrongPsk) => Ok(ConnectOutcome::WrongPsk),
+            Ok(SelectResult::NotFound) => Ok(ConnectOutcome::NotFound),
+            Ok(_) => Err(error::Error::WifiSelect),
+            Err(error::Error::RequestTimeout) => Ok(ConnectOutcome::Timeout),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Drops the current association while keeping the selected network configured.
+    /// Resolves once wpa_supplicant reports the disconnection.
+    pub async fn disconnect(&self) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Disconnect(response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// Checks that wpa_supplicant answers on the control socket
+    pub async fn ping(&self) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Ping(response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// Re-triggers association after a `disconnect`. Resolves once wpa_supplicant
+    /// acknowledges the command, not once the connection is established.
+    /// Does nothing while already connected, see `reassociate` for that.
+    pub async fn reconnect(&self) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Reconnect(response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// Forces a new association even while connected, eg: to recover a link that is
+    /// associated but not passing traffic. Unlike `reconnect`, this drops a working
+    /// connection. Resolves once wpa_supplicant acknowledges the command.
+    pub async fn reassociate(&self) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Reassociate(response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// Changes the PSK of the current network and reassociates so that the new key is
+    /// used right away, resolving once the station is connected again. A new key the
+    /// access point doesn't accept is reported as `ConnectOutcome::WrongPsk`. `network_id`
+    /// should be the current network, since reassociating always applies to that one.
+    pub async fn rotate_psk(&self, network_id: usize, new_psk: String) -> Result<ConnectOutcome> {
+        self.set_network_psk(network_id, new_psk).await?;
+        let outcome = self.wait_for(
+            |broadcast| {
+                matches!(
+                    broadcast,
+                    Broadcast::Connected | Broadcast::WrongPsk | Broadcast::NetworkNotFound
+                )
+            },
+            self.timeout,
+        );
+        self.reassociate().await?;
+        match outcome.await {
+            Ok(Broadcast::WrongPsk) => Ok(ConnectOutcome::WrongPsk),
+            Ok(Broadcast::NetworkNotFound) => Ok(ConnectOutcome::NotFound),
+            Ok(_) => Ok(ConnectOutcome::Connected),
+            Err(error::Error::Timeout) => Ok(ConnectOutcome::Timeout),
+            Err(e) => Err(e),
+        }
     }
 
+    /// Starts WPS push-button configuration. Resolves once the exchange completes, or
+    /// with `WpsResult::Timeout` if no registrar responds within the WPS walk time, in
+    /// which case the exchange is cancelled. Fails with `Error::WpsInProgress` while
+    /// another caller awaits an exchange.
+    pub async fn wps_pbc(&self) -> Result<WpsResult> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::WpsPbc(response)).await?;
+        match tokio::time::timeout(WPS_TIMEOUT, request).await {
+            Ok(result) => result?,
+            Err(_) => {
+                if let Err(e) = self.expect_ok("WPS_CANCEL").await {
+                    warn!("Error while cancelling WPS PBC: {e}");
+                }
+                Ok(WpsResult::Timeout)
+            }
+        }
+    }
+
+    /// Starts WPS PIN configuration. When `pin` is `None`, wpa_supplicant generates a PIN
+    /// which is returned so it can be shown to the user. A provided PIN must be 4 or 8 digits.
+    pub async fn wps_pin(&self, pin: Option<String>) -> Result<String> {
+        if let Some(pin) = &pin {
+            if !(pin.len() == 4 || pin.len() == 8) || !pin.chars().all(|c| c.is_ascii_digit()) {
+                return Err(error::Error::InvalidWpsPin(pin.clone()));
+            }
+        }
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::WpsPin(pin, response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// Pins the network to a specific BSSID and selects it. The BSSID must be present in
+    /// the latest scan results. Passing `Bssid::ANY` clears a previous pin.
+    pub async fn select_bssid<B: Into<Bssid>>(
+        &self,
+        network_id: usize,
+        bssid: B,
+    ) -> Result<SelectResult> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::SelectBssid(network_id, bssid.into(), response))
+            .await?;
+        self.await_response(request).await
+    }
+
+    /// Roams to another BSS of the current network, which must be present in the latest
+    /// scan results
+    pub async fn roam<B: Into<Bssid>>(&self, bssid: B) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Roam(bssid.into(), response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Sends an arbitrary control command and returns the reply text unmodified, for
+    /// commands that have no dedicated method. Replies such as `FAIL` are not interpreted.
+    pub async fn raw_command(&self, cmd: &str) -> Result<String> {
+        if cmd.is_empty() || cmd.contains(['\n', '\r']) {
+            return Err(error::Error::InvalidCommand(cmd.to_string()));
+        }
+        self.raw(cmd).await
+    }
+
+    /// Lists what wpa_supplicant and the driver support for a kind of capability, eg:
+    /// `pairwise`, `group`, `key_mgmt`, `proto`, `auth_alg` or `eap`, as issued by
+    /// `GET_CAPABILITY`. Unknown kinds fail with `Error::UnexpectedWifiApRepsonse`.
+    pub async fn get_capability(&self, kind: &str) -> Result<Vec<String>> {
+        if kind.is_empty() || kind.contains(char::is_whitespace) {
+            return Err(error::Error::InvalidCommand(kind.to_string()));
+        }
+        let resp = self.capability_response(kind).await?;
+        Ok(resp.split_whitespace().map(str::to_string).collect())
+    }
+
+    async fn capability_response(&self, kind: &str) -> Result<String> {
+        match self.raw(format!("GET_CAPABILITY {kind}")).await? {
+            resp if resp.starts_with("FAIL") => Err(error::Error::UnexpectedWifiApRepsonse(resp)),
+            resp => Ok(resp),
+        }
+    }
+
+    /// Everything wpa_supplicant and the driver support, queried once and cached for the
+    /// lifetime of the runtime since capabilities don't change. Kinds wpa_supplicant can't
+    /// report, eg: `eap` when built without 802.1X support, are left empty.
+    pub async fn capabilities(&self) -> Result<Capabilities> {
+        let capabilities = self
+            .capabilities
+            .get_or_try_init(|| async {
+                let mut responses = Vec::new();
+                for kind in Capabilities::KINDS {
+                    match self.capability_response(kind).await {
+                        Ok(resp) => responses.push(resp),
+                        Err(error::Error::UnexpectedWifiApRepsonse(_)) => {
+                            responses.push(String::new())
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                Capabilities::from_responses(&responses)
+            })
+            .await?;
+        Ok(capabilities.clone())
+    }
+
+    /// EAP methods wpa_supplicant was built with, eg: `TLS`, `PEAP` or `TTLS`
+    pub async fn get_eap_methods(&self) -> Result<Vec<String>> {
+        self.get_capability("eap").await
+    }
+
+    /// Drops every BSS entry cached by wpa_supplicant, except the one in use. Flushing
+    /// often slows roaming decisions, as wpa_supplicant then knows of no other access
+    /// point of the network until the next scan.
+    pub async fn flush_bss(&self) -> Result {
+        self.expect_ok("BSS_FLUSH 0").await
+    }
+
+    /// Aborts the scan in flight, eg: to connect without waiting for it, returning whether
+    /// there was one. Pending `get_scan` calls are answered with whatever results the
+    /// aborted scan reports.
+    pub async fn abort_scan(&self) -> Result<bool> {
+        match self.raw("ABORT_SCAN").await? {
+            resp if resp == "OK" => Ok(true),
+            // wpa_supplicant fails the command when no scan is running
+            resp if resp.starts_with("FAIL") => Ok(false),
+            resp => Err(error::Error::UnexpectedWifiApRepsonse(resp)),
+        }
+    }
+
+    /// Sets who scans for and selects access points. A mode the driver does not support
+    /// is a classic cause of never connecting, in which case `ApScanMode::Supplicant`
+    /// is usually the right choice.
+    pub async fn set_ap_scan(&self, mode: ApScanMode) -> Result {
+        self.expect_ok(format!("AP_SCAN {}", mode.value())).await
+    }
+
+    /// Starts managing the interface `ifname`, optionally with a driver such as `nl80211` and
+    /// a configuration file. This is a global command, so the runtime must control the
+    /// global socket: a per-interface socket answers `UNKNOWN COMMAND`.
+    ///
+    /// The new interface gets a control socket in the default directory, whose path is
+    /// returned for `WifiSetup::set_socket_path`.
+    pub async fn interface_add(
+        &self,
+        ifname: &str,
+        driver: Option<&str>,
+        conf: Option<&str>,
+    ) -> Result<std::path::PathBuf> {
+        let args = [ifname, conf.unwrap_or_default(), driver.unwrap_or_default()];
+        if ifname.is_empty()
+            || ifname.contains(char::is_whitespace)
+            || args.iter().any(|arg| arg.contains(['\t', '\n']))
+        {
+            return Err(error::Error::InvalidCommand(args.join(" ")));
+        }
+        let [ifname, conf, driver] = args;
+        // fields are tab separated: ifname, confname, driver, ctrl_interface
+        let cmd = format!("INTERFACE_ADD {ifname}\t{conf}\t{driver}\t{SOCKET_DIR_DEFAULT}");
+        self.expect_ok(cmd).await?;
+        Ok(std::path::Path::new(SOCKET_DIR_DEFAULT).join(ifname))
+    }
+
+    /// Stops managing the interface `ifname`, a global command like `interface_add`
+    pub async fn interface_remove(&self, ifname: &str) -> Result {
+        if ifname.is_empty() || ifname.contains(char::is_whitespace) {
+            return Err(error::Error::InvalidCommand(ifname.to_string()));
+        }
+        self.expect_ok(format!("INTERFACE_REMOVE {ifname}")).await
+    }
+
+    /// BSSIDs wpa_supplicant currently avoids after failed connections. Entries expire on
+    /// their own, see `clear_blacklist` to drop them right away. wpa_supplicant 2.10
+    /// renamed the command to `BSSID_IGNORE`, keeping `BLACKLIST` as an alias.
+    pub async fn get_blacklist(&self) -> Result<Vec<Bssid>> {
+        let resp = self.raw_checked("BLACKLIST").await?;
+        resp.split_whitespace()
+            .map(|bssid| error::parse_value("BLACKLIST", &resp, bssid))
+            .collect()
+    }
+
+    /// Avoids the access point, eg: a flaky one keeping the station from a better BSS
+    pub async fn blacklist_add<B: Into<Bssid>>(&self, bssid: B) -> Result {
+        let cmd = format!("BLACKLIST {}", bssid.into());
+        self.raw_checked(cmd).await.map(|_| ())
+    }
+
+    pub async fn clear_blacklist(&self) -> Result {
+        self.raw_checked("BLACKLIST clear").await.map(|_| ())
+    }
+
+    /// Sets a global wpa_supplicant variable, issuing `SET <key> <value>`
+    async fn set(&self, key: &str, value: &str) -> Result {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Set(key.into(), value.into(), response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Reads a global wpa_supplicant variable, issuing `GET <key>`
+    async fn get(&self, key: &str) -> Result<String> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::Get(key.into(), response))
+            .await?;
+        self.await_response(request).await?
+    }
+
+    /// Sets the regulatory domain to an ISO 3166-1 alpha-2 country code, eg: `US`.
+    /// The setting is lost when wpa_supplicant restarts unless followed by `save_config`.
+    pub async fn set_country(&self, code: &str) -> Result {
+        if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(error::Error::InvalidCountryCode(code.to_string()));
+        }
+        self.set("country", &code.to_ascii_uppercase()).await
+    }
+
+    /// Restricts the groups offered for SAE (WPA3-Personal), in order of preference, eg:
+    /// `&[19, 20, 21]` for some access points that only support certain curves. Groups
+    /// must be in the IANA registry of ECC or FFC groups, and an empty slice restores
+    /// wpa_supplicant's default.
+    pub async fn set_sae_groups(&self, groups: &[u16]) -> Result {
+        if let Some(group) = groups.iter().find(|group| !SAE_GROUPS.contains(group)) {
+            return Err(error::Error::InvalidSaeGroup(*group));
+        }
+        let groups: Vec<String> = groups.iter().map(u16::to_string).collect();
+        self.set("sae_groups", &groups.join(" ")).await
+    }
+
+    /// Reads the current regulatory domain country code
+    pub async fn get_country(&self) -> Result<String> {
+        self.get("country").await
+    }
+
+    /// Sets how often wpa_supplicant scans while disconnected, eg: `periodic:30`. Intervals
+    /// must be non-zero, and an exponential `limit` at least its `base`, which must exceed 1.
+    /// Requires wpa_supplicant to be built with `CONFIG_AUTOSCAN`.
+    pub async fn set_autoscan(&self, cfg: AutoscanConfig) -> Result {
+        if !cfg.is_valid() {
+            return Err(error::Error::InvalidAutoscan(cfg.to_string()));
+        }
+        self.expect_ok(format!("AUTOSCAN {cfg}")).await
+    }
+
+    /// Returns to wpa_supplicant's default scanning schedule, see `set_autoscan`
+    pub async fn disable_autoscan(&self) -> Result {
+        // wpa_supplicant only knows the command with its trailing space
+        self.expect_ok("AUTOSCAN ").await
+    }
+
+    /// Changes the verbosity of wpa_supplicant's debug log, keeping the timestamp setting
+    pub async fn set_log_level(&self, level: LogLevel) -> Result {
+        self.expect_ok(format!("LOG_LEVEL {level}")).await
+    }
+
+    /// Reads the verbosity of wpa_supplicant's debug log and whether it is timestamped
+    pub async fn get_log_level(&self) -> Result<LogSettings> {
+        LogSettings::from_response(&self.raw("LOG_LEVEL").await?)
+    }
+
+    /// Limits the transmit power to `dbm`, or lets the driver choose with `None`.
+    /// wpa_supplicant has no command of its own for this, so it is passed to the driver
+    /// as `DRIVER TXPOWER`, which only some vendor drivers implement. Others fail with
+    /// `Error::Unsupported`.
+    pub async fn set_tx_power(&self, dbm: Option<i32>) -> Result {
+        let value = dbm.map_or("auto".to_string(), |dbm| dbm.to_string());
+        self.supported_driver_command(&format!("TXPOWER {value}"))
+            .await
+            .map(|_| ())
+    }
+
+    /// Reads the transmit power in dBm, `None` when chosen by the driver. Like
+    /// `set_tx_power`, this requires driver support.
+    pub async fn get_tx_power(&self) -> Result<Option<i32>> {
+        let resp = self.supported_driver_command("TXPOWER").await?;
+        match resp.as_str() {
+            "auto" => Ok(None),
+            dbm => Ok(Some(error::parse_value("DRIVER TXPOWER", &resp, dbm)?)),
+        }
+    }
+
+    /// Puts the radio into its low power suspend state, or back out of it, through the
+    /// vendor `DRIVER SETSUSPENDMODE` command. Drivers without it fail with
+    /// `Error::Unsupported`.
+    pub async fn set_suspend(&self, suspend: bool) -> Result {
+        self.supported_driver_command(&format!("SETSUSPENDMODE {}", u8::from(suspend)))
+            .await
+            .map(|_| ())
+    }
+
+    /// Terminates wpa_supplicant. `reconfigure` resets the connection in place, but
+    /// wpa_supplicant has no command for re-initializing the driver itself, whereas
+    /// terminating it does when a supervisor respawns it, eg: systemd with
+    /// `Restart=always`. With a `ReconnectPolicy` the runtime then reattaches to the new
+    /// instance, otherwise it fails on the next request.
+    pub async fn terminate(&self) -> Result {
+        self.expect_ok("TERMINATE").await
+    }
+
+    /// A driver command for a specific feature, reporting a `FAIL` as `Error::Unsupported`
+    async fn supported_driver_command(&self, cmd: &str) -> Result<String> {
+        self.driver_command(cmd).await.map_err(|e| match e {
+            error::Error::UnexpectedWifiApRepsonse(_) => {
+                let name = cmd.split_whitespace().next().unwrap_or_default();
+                error::Error::Unsupported(format!("DRIVER {name}"))
+            }
+            e => e,
+        })
+    }
+
+    /// Passes a vendor specific command to the driver as `DRIVER <cmd>`, eg: `RXFILTER-ADD 2`
+    /// on Android derived stacks, returning its reply. Which commands exist depends entirely
+    /// on the driver; a `FAIL` reply is returned as `Error::UnexpectedWifiApRepsonse`.
+    pub async fn driver_command(&self, cmd: &str) -> Result<String> {
+        if cmd.trim().is_empty() || cmd.contains(['\n', '\r']) {
+            return Err(error::Error::InvalidCommand(cmd.to_string()));
+        }
+        let resp = self.raw(format!("DRIVER {cmd}")).await?;
+        if resp.starts_with("FAIL") {
+            return Err(error::Error::UnexpectedWifiApRepsonse(resp));
+        }
+        Ok(resp)
+    }
+
+    /// Stops the runtime, returning once it has exited and closed its control sockets.
+    /// Shutdown is accepted while the control socket is disconnected.
     pub async fn shutdown(&self) -> Result {
-        self.send_request(Request::Shutdown).await?;
-        Ok(())
+        let (response, request) = oneshot::channel();
+        self.sender
+            .send(Request::Shutdown(response))
+            .await
+            .map_err(|_| error::Error::WifiStationRequestChannelClosed)?;
+        self.await_response(request).await
+    }
+}
+
+/// Removes the network added by `connect` or `add_and_select` when the caller drops the
+/// future midway. Cleanup is best effort: a destructor can't wait, so the removal is skipped
+/// with a warning when the request channel is full.
+struct NetworkGuard<'a> {
+    client: &'a RequestClient,
+    network_id: Option<usize>,
+}
+
+impl Drop for NetworkGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(network_id) = self.network_id.take() {
+            let request = Request::RemoveNetwork(network_id);
+            if self.client.sender.try_send(request).is_err() {
+                warn!("Unable to remove network {network_id} after failing to select it");
+            }
+        }
     }
 }
 
+/// Details of a `CTRL-EVENT-DISCONNECTED` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DisconnectInfo {
+    pub bssid: Bssid,
+    /// IEEE 802.11 reason code, eg: 3 when leaving the network or 15 on a 4-way handshake timeout
+    pub reason_code: u16,
+    /// Whether the disconnection was initiated by this station rather than the AP
+    pub locally_generated: bool,
+}
+
 /// Broadcast events are unexpected, such as losing connection to the host network.
+///
+/// Since 0.2, `Disconnected` carries the details of the disconnection.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Broadcast {
+    Connected,
+    Disconnected(DisconnectInfo),
+    NetworkNotFound,
+    WrongPsk,
+    Ready,
+    /// The access point refused the association with an 802.11 status code,
+    /// eg: 17 when it can't take more stations
+    AssocReject {
+        bssid: Bssid,
+        status_code: u16,
+    },
+    /// 802.1X authentication started with the access point
+    EapStarted,
+    EapSuccess,
+    /// 802.1X authentication failed, eg: rejected credentials or an untrusted server
+    EapFailure,
+    /// The WPA 4-way handshake failed, usually because of a wrong passphrase
+    HandshakeFailed,
+    /// wpa_supplicant stopped trying the network for a while after failed
+    /// authentications. `reason` is eg: `WRONG_KEY`, which is also broadcast as `WrongPsk`,
+    /// or `AUTH_FAILED`. The network is retried once the back-off expires, or right away
+    /// when enabled again.
+    NetworkTempDisabled {
+        network_id: usize,
+        auth_failures: u32,
+        reason: String,
+    },
+    /// wpa_supplicant finished a scan, whether requested by a client or run in the
+    /// background, and `get_scan` will return the new results
+    ScanResultsAvailable,
+    /// Progress of DPP (Wi-Fi Easy Connect) provisioning
+    Dpp(DppEvent),
+    /// Coarse progress of connecting in percent, see `WpaState::connection_progress`.
+    /// Sent whenever it changes, and derived from the events of wpa_supplicant: builds
+    /// which don't report every state change go from associated straight to 100.
+    ConnectionProgress(u8),
+}
+
+/// The variant of a `Broadcast` without its details, for selecting broadcasts with
+/// `WifiSetup::broadcast_filtered`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BroadcastKind {
     Connected,
     Disconnected,
     NetworkNotFound,
     WrongPsk,
     Ready,
+    AssocReject,
+    EapStarted,
+    EapSuccess,
+    EapFailure,
+    HandshakeFailed,
+    NetworkTempDisabled,
+    ScanResultsAvailable,
+    Dpp,
+    ConnectionProgress,
+}
+
+impl Broadcast {
+    pub fn kind(&self) -> BroadcastKind {
+        match self {
+            Broadcast::Connected => BroadcastKind::Connected,
+            Broadcast::Disconnected(_) => BroadcastKind::Disconnected,
+            Broadcast::NetworkNotFound => BroadcastKind::NetworkNotFound,
+            Broadcast::WrongPsk => BroadcastKind::WrongPsk,
+            Broadcast::Ready => BroadcastKind::Ready,
+            Broadcast::AssocReject { .. } => BroadcastKind::AssocReject,
+            Broadcast::EapStarted => BroadcastKind::EapStarted,
+            Broadcast::EapSuccess => BroadcastKind::EapSuccess,
+            Broadcast::EapFailure => BroadcastKind::EapFailure,
+            Broadcast::HandshakeFailed => BroadcastKind::HandshakeFailed,
+            Broadcast::NetworkTempDisabled { .. } => BroadcastKind::NetworkTempDisabled,
+            Broadcast::ScanResultsAvailable => BroadcastKind::ScanResultsAvailable,
+            Broadcast::Dpp(_) => BroadcastKind::Dpp,
+            Broadcast::ConnectionProgress(_) => BroadcastKind::ConnectionProgress,
+        }
+    }
 }
 
 /// Channel for broadcasting events. Subscribing to this channel is equivalent to
 /// "wpa_ctrl_attach". Can be temporarily silenced using broadcast::Receiver's unsubscribe
 pub type BroadcastReceiver = broadcast::Receiver<Broadcast>;
+
+/// Channel for the verbatim event lines, see `WifiSetup::raw_events`
+pub type RawEventReceiver = broadcast::Receiver<String>;
@@ -0,0 +1,97 @@
+use super::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// DPP (Wi-Fi Easy Connect) events, broadcast as `Broadcast::Dpp`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DppEvent {
+    /// Authentication with the peer succeeded, configuration follows
+    AuthSuccess,
+    AuthFailed,
+    /// A configuration object was received from the configurator
+    ConfReceived,
+    ConfFailed,
+    /// SSID of the network in the received configuration
+    ConfObjSsid(String),
+    /// Key management of the network in the received configuration, eg: `psk` or `dpp`
+    ConfObjAkm(String),
+    /// Id of the network wpa_supplicant added from the received configuration, when
+    /// `dpp_config_processing` is enabled
+    NetworkId(usize),
+    /// Any other failure, with wpa_supplicant's explanation
+    Fail(String),
+}
+
+impl DppEvent {
+    /// Parses a DPP event line, `None` if it isn't one of the supported events. The
+    /// passphrase of a received configuration is deliberately not parsed.
+    pub(crate) fn from_event(data_str: &str) -> Option<DppEvent> {
+        let argument = |event: &str| {
+            let start = data_str.find(event)? + event.len();
+            Some(data_str[start..].trim().to_string())
+        };
+        if data_str.contains("DPP-AUTH-SUCCESS") {
+            Some(DppEvent::AuthSuccess)
+        } else if data_str.contains("DPP-AUTH-INIT-FAILED")
+            || data_str.contains("DPP-NOT-COMPATIBLE")
+        {
+            Some(DppEvent::AuthFailed)
+        } else if data_str.contains("DPP-CONF-RECEIVED") {
+            Some(DppEvent::ConfReceived)
+        } else if data_str.contains("DPP-CONF-FAILED") {
+            Some(DppEvent::ConfFailed)
+        } else if data_str.contains("DPP-CONFOBJ-SSID ") {
+            argument("DPP-CONFOBJ-SSID ").map(DppEvent::ConfObjSsid)
+        } else if data_str.contains("DPP-CONFOBJ-AKM ") {
+            argument("DPP-CONFOBJ-AKM ").map(DppEvent::ConfObjAkm)
+        } else if data_str.contains("DPP-NETWORK-ID ") {
+            argument("DPP-NETWORK-ID ")?
+                .parse()
+                .ok()
+                .map(DppEvent::NetworkId)
+        } else if data_str.contains("DPP-FAIL ") {
+            argument("DPP-FAIL ").map(DppEvent::Fail)
+        } else {
+            None
+        }
+    }
+}
+
+impl RequestClient {
+    /// Adds the bootstrapping information of a peer scanned from its QR code, eg:
+    /// `DPP:K:MDkw...;;`, returning its id for `dpp_auth_init`. DPP requires
+    /// wpa_supplicant to be built with `CONFIG_DPP`.
+    pub async fn dpp_qr_code_add(&self, uri: &str) -> Result<usize> {
+        if !uri.starts_with("DPP:") || uri.contains(char::is_whitespace) {
+            return Err(error::Error::InvalidCommand(uri.to_string()));
+        }
+        let resp = self.raw_checked(format!("DPP_QR_CODE {uri}")).await?;
+        error::parse_value("DPP_QR_CODE", &resp, &resp)
+    }
+
+    /// Waits on the channel of `freq` MHz for a configurator to initiate authentication,
+    /// typically 2437 for channel 6 when the device displays its own QR code
+    pub async fn dpp_listen(&self, freq: u32) -> Result {
+        if frequency_to_channel(freq).is_none() {
+            return Err(error::Error::InvalidFrequency(freq));
+        }
+        self.raw_checked(format!("DPP_LISTEN {freq}"))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn dpp_stop_listen(&self) -> Result {
+        self.raw_checked("DPP_STOP_LISTEN".to_string())
+            .await
+            .map(|_| ())
+    }
+
+    /// Initiates authentication with the peer added by `dpp_qr_code_add`, as an enrollee
+    /// requesting a configuration. `DppEvent::ConfReceived` follows on success.
+    pub async fn dpp_auth_init(&self, peer: usize) -> Result {
+        self.raw_checked(format!("DPP_AUTH_INIT peer={peer} role=enrollee"))
+            .await
+            .map(|_| ())
+    }
+}
@@ -1,36 +1,75 @@
 use super::*;
 
 pub(crate) struct EventSocket {
-    socket_handle: SocketHandle<256>,
+    /// Sized like wpa_supplicant's own buffer, eg: `P2P-DEVICE-FOUND` lines exceed 256 bytes
+    socket_handle: SocketHandle<4096>,
     /// Sends messages to client
     sender: mpsc::Sender<Event>,
+    /// Interface whose events are forwarded, or every interface's when unset
+    interface: Option<String>,
+    /// Every event line of the interface, verbatim
+    raw_sender: broadcast::Sender<String>,
 }
 
 #[derive(Debug)]
 pub(crate) enum Event {
+    ScanStarted,
+    /// Carries the `ret=` code, eg: -16 when the driver was busy
+    ScanFailed(Option<i32>),
     ScanComplete,
     Connected,
-    Disconnected,
+    Disconnected(DisconnectInfo),
     NetworkNotFound,
     WrongPsk,
+    AssocReject {
+        bssid: Bssid,
+        status_code: u16,
+    },
+    NetworkTempDisabled {
+        network_id: usize,
+        auth_failures: u32,
+        reason: String,
+    },
+    EapStarted,
+    EapSuccess,
+    EapFailure,
+    HandshakeFailed,
+    StateChange(WpaState),
+    WpsSuccess,
+    WpsTimeout,
+    WpsOverlap,
+    WpsFail,
+    P2p(P2pEvent),
+    Dpp(DppEvent),
 }
 
 pub(crate) type EventReceiver = mpsc::Receiver<Event>;
 
+/// Finds the value of a `key=value` parameter in an event line
+fn event_param<'a>(data_str: &'a str, key: &str) -> Option<&'a str> {
+    data_str
+        .split_whitespace()
+        .filter_map(|param| param.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, value)| value)
+}
+
 impl EventSocket {
-    pub(crate) async fn new<P>(socket: P) -> Result<(EventReceiver, Self)>
-    where
-        P: AsRef<std::path::Path> + std::fmt::Debug,
-    {
-        let socket_handle = SocketHandle::open(socket, "mapper_wpa_ctrl_async.sock").await?;
+    pub(crate) fn new(
+        socket_handle: SocketHandle<4096>,
+        interface: Option<String>,
+        raw_sender: broadcast::Sender<String>,
+    ) -> (EventReceiver, Self) {
         let (sender, receiver) = mpsc::channel(32);
-        Ok((
+        (
             receiver,
             Self {
                 socket_handle,
                 sender,
+                interface,
+                raw_sender,
             },
-        ))
+        )
     }
 
     async fn send_event(&self, event: Event) -> Result {
@@ -53,8 +92,37 @@ impl EventSocket {
                 .await
             {
                 Ok(n) => {
-                    let data_str = std::str::from_utf8(&self.socket_handle.buffer[..n])?.trim_end();
-                    debug!("wpa_ctrl event: {data_str}");
+                    // SSIDs are escaped, but a truncated line mustn't stop the event loop
+                    let text = String::from_utf8_lossy(&self.socket_handle.buffer[..n]);
+                    let line = text.trim_end();
+                    debug!("wpa_ctrl event: {line}");
+                    // the global control interface prefixes events with the interface name,
+                    // which only filters them when the interface was chosen explicitly
+                    let data_str = match line
+                        .strip_prefix("IFNAME=")
+                        .and_then(|rest| rest.split_once(' '))
+                    {
+                        Some((ifname, _))
+                            if self.interface.as_deref().is_some_and(|i| i != ifname) =>
+                        {
+                            continue;
+                        }
+                        Some((_, event)) => event,
+                        None => line,
+                    };
+                    // events carry a priority prefix, eg: <3>, unlike the reply to ATTACH.
+                    // Nobody subscribing to raw events is the usual case, so sending may fail.
+                    if data_str.starts_with('<') {
+                        let _ = self.raw_sender.send(line.to_string());
+                    }
+                    if data_str.contains("CTRL-EVENT-SCAN-STARTED") {
+                        self.send_event(Event::ScanStarted).await?;
+                    }
+                    if data_str.contains("CTRL-EVENT-SCAN-FAILED") {
+                        let ret =
+                            event_param(data_str, "ret").and_then(|ret| i32::from_str(ret).ok());
+                        self.send_event(Event::ScanFailed(ret)).await?;
+                    }
                     if data_str.ends_with("CTRL-EVENT-SCAN-RESULTS") {
                         self.send_event(Event::ScanComplete).await?;
                     }
@@ -62,7 +130,28 @@ impl EventSocket {
                         self.send_event(Event::Connected).await?;
                     }
                     if data_str.contains("CTRL-EVENT-DISCONNECTED") {
-                        self.send_event(Event::Disconnected).await?;
+                        let info = DisconnectInfo {
+                            bssid: event_param(data_str, "bssid")
+                                .and_then(|bssid| Bssid::from_str(bssid).ok())
+                                .unwrap_or_default(),
+                            reason_code: event_param(data_str, "reason")
+                                .and_then(|reason| u16::from_str(reason).ok())
+                                .unwrap_or_default(),
+                            locally_generated: event_param(data_str, "locally_generated")
+                                == Some("1"),
+                        };
+                        self.send_event(Event::Disconnected(info)).await?;
+                    }
+                    if data_str.contains("CTRL-EVENT-ASSOC-REJECT") {
+                        let event = Event::AssocReject {
+                            bssid: event_param(data_str, "bssid")
+                                .and_then(|bssid| Bssid::from_str(bssid).ok())
+                                .unwrap_or_default(),
+                            status_code: event_param(data_str, "status_code")
+                                .and_then(|status| u16::from_str(status).ok())
+                                .unwrap_or_default(),
+                        };
+                        self.send_event(event).await?;
                     }
                     if data_str.contains("CTRL-EVENT-NETWORK-NOT-FOUND") {
                         self.send_event(Event::NetworkNotFound).await?;
@@ -72,6 +161,67 @@ impl EventSocket {
                     {
                         self.send_event(Event::WrongPsk).await?;
                     }
+                    if data_str.contains("CTRL-EVENT-SSID-TEMP-DISABLED") {
+                        let event = Event::NetworkTempDisabled {
+                            network_id: event_param(data_str, "id")
+                                .and_then(|id| usize::from_str(id).ok())
+                                .unwrap_or_default(),
+                            auth_failures: event_param(data_str, "auth_failures")
+                                .and_then(|failures| u32::from_str(failures).ok())
+                                .unwrap_or_default(),
+                            reason: event_param(data_str, "reason")
+                                .unwrap_or_default()
+                                .to_string(),
+                        };
+                        self.send_event(event).await?;
+                    }
+                    // the trailing spaces keep the numbered variants, eg: EAP-FAILURE2, apart
+                    if data_str.contains("CTRL-EVENT-EAP-STARTED ") {
+                        self.send_event(Event::EapStarted).await?;
+                    }
+                    if data_str.contains("CTRL-EVENT-EAP-SUCCESS ") {
+                        self.send_event(Event::EapSuccess).await?;
+                    }
+                    if data_str.contains("CTRL-EVENT-EAP-FAILURE ") {
+                        self.send_event(Event::EapFailure).await?;
+                    }
+                    if data_str.contains("WPA: 4-Way Handshake failed") {
+                        self.send_event(Event::HandshakeFailed).await?;
+                    }
+                    // only some builds, eg: Android's, report every state change, the others
+                    // log the steps of connecting at the default level
+                    let state = if data_str.contains("CTRL-EVENT-STATE-CHANGE") {
+                        event_param(data_str, "state").map(WpaState::from_index)
+                    } else if data_str.contains("SME: Trying to authenticate with") {
+                        Some(WpaState::Authenticating)
+                    } else if data_str.contains("Trying to associate with") {
+                        Some(WpaState::Associating)
+                    } else if data_str.contains("Associated with") {
+                        Some(WpaState::Associated)
+                    } else {
+                        None
+                    };
+                    if let Some(state) = state {
+                        self.send_event(Event::StateChange(state)).await?;
+                    }
+                    if data_str.contains("WPS-SUCCESS") {
+                        self.send_event(Event::WpsSuccess).await?;
+                    }
+                    if data_str.contains("WPS-TIMEOUT") {
+                        self.send_event(Event::WpsTimeout).await?;
+                    }
+                    if data_str.contains("WPS-OVERLAP-DETECTED") {
+                        self.send_event(Event::WpsOverlap).await?;
+                    }
+                    if data_str.contains("WPS-FAIL") {
+                        self.send_event(Event::WpsFail).await?;
+                    }
+                    if let Some(event) = P2pEvent::from_event(data_str) {
+                        self.send_event(Event::P2p(event)).await?;
+                    }
+                    if let Some(event) = DppEvent::from_event(data_str) {
+                        self.send_event(Event::Dpp(event)).await?;
+                    }
                 }
                 Err(e) => {
                     return Err(error::Error::UnsolicitedIoError(e));
@@ -12,51 +12,222 @@ pub use setup::*;
 mod event_socket;
 use event_socket::*;
 
+mod p2p;
+pub use p2p::*;
+
+mod dpp;
+pub use dpp::*;
+
+#[cfg(feature = "blocking")]
+mod blocking;
+#[cfg(feature = "blocking")]
+pub use blocking::*;
+
+const SOCKET_DIR_DEFAULT: &str = "/var/run/wpa_supplicant";
 const PATH_DEFAULT_SERVER: &str = "/var/run/wpa_supplicant/wlan2";
+const SYNC_SOCKET_LABEL: &str = "mapper_wpa_ctrl_sync.sock";
+const ASYNC_SOCKET_LABEL: &str = "mapper_wpa_ctrl_async.sock";
 
 /// Instance that runs the Wifi process
 pub struct WifiStation {
     /// Path to the socket
     socket_path: std::path::PathBuf,
+    /// Interface set with `set_interface`, whose events are the only ones forwarded
+    /// from a socket prefixing them with `IFNAME=`
+    interface: Option<String>,
     /// Channel for receiving requests
     request_receiver: mpsc::Receiver<Request>,
     #[allow(unused)]
     /// Channel for broadcasting alerts
     broadcast_sender: broadcast::Sender<Broadcast>,
+    /// Channel for broadcasting P2P events
+    p2p_broadcast_sender: broadcast::Sender<P2pEvent>,
+    /// Channel for broadcasting every event line verbatim
+    raw_broadcast_sender: broadcast::Sender<String>,
+    /// How to recover from losing the control socket, if at all
+    reconnect_policy: Option<ReconnectPolicy>,
+    /// Shared with the request clients so they fail fast while the control socket is lost
+    disconnected: Arc<AtomicBool>,
+    /// Acknowledges a shutdown request once the sockets are closed
+    shutdown_ack: Option<oneshot::Sender<()>>,
+    /// Whether scan requests arriving during a scan await its results instead of
+    /// starting a new one
+    coalesce_scans: bool,
+}
+
+/// Per-interface control sockets are named after the interface they control, unlike the
+/// global control socket, conventionally `global` or `wpa_supplicant-global`
+fn interface_name<P: AsRef<std::path::Path>>(socket_path: P) -> Option<String> {
+    let name = socket_path.as_ref().file_name()?.to_string_lossy();
+    if name == "global" || name.ends_with("-global") {
+        return None;
+    }
+    Some(name.into_owned())
+}
+
+/// Resolves once every sender is closed, ie: every caller stopped waiting
+async fn all_closed<T>(senders: &mut [oneshot::Sender<T>]) {
+    for sender in senders {
+        sender.closed().await;
+    }
+}
+
+/// Requests answered by the runtime once an event is observed
+#[derive(Default)]
+struct PendingRequests {
+    /// We will collect scan requests and batch respond to them when results are ready
+    scan_requests: Vec<oneshot::Sender<Result<ScanOutcome>>>,
+    /// Tracks scans in flight, including those started by wpa_supplicant itself
+    scan_in_progress: bool,
+    /// The network being selected along with the caller awaiting the outcome
+    select_request: Option<(usize, oneshot::Sender<SelectResult>)>,
+    /// Disconnect requests are answered once the supplicant confirms with an event
+    disconnect_requests: Vec<oneshot::Sender<Result>>,
+    /// The WPS exchange in progress, one at a time as wpa_supplicant runs a single one
+    wps_request: Option<oneshot::Sender<Result<WpsResult>>>,
 }
 
 impl WifiStation {
-    pub async fn run(self) -> Result {
+    pub async fn run(mut self) -> Result {
         info!("Starting Wifi Station process");
+        let resp = self.run_sockets().await;
+        // the sockets are closed by now, so the caller of shutdown may proceed
+        if let Some(ack) = self.shutdown_ack.take() {
+            if ack.send(()).is_err() {
+                error!("Shutdown response channel closed before response sent");
+            }
+        }
+        resp
+    }
 
-        tokio::select!(
-            resp = async move {
-                let socket_handle =
-                    SocketHandle::open(&self.socket_path, "mapper_wpa_ctrl_sync.sock").await?;
-                // We start up a separate socket for receiving the "unexpected" events that
-                // gets forwarded to us via the unsolicited_receiver
-                let (unsolicited_receiver, unsolicited) = EventSocket::new(&self.socket_path).await?;
-                self.broadcast_sender.send(Broadcast::Ready)?;
+    async fn run_sockets(&mut self) -> Result {
+        let socket_handle = SocketHandle::open(&self.socket_path, SYNC_SOCKET_LABEL).await?;
+        // We start up a separate socket for receiving the "unexpected" events that
+        // gets forwarded to us via the unsolicited_receiver
+        let event_socket_handle = SocketHandle::open(&self.socket_path, ASYNC_SOCKET_LABEL).await?;
+        let mut sockets = (socket_handle, event_socket_handle);
+        loop {
+            let resp = self.run_attached(sockets.0, sockets.1).await;
+            let policy = match (&resp, self.reconnect_policy) {
+                (Ok(()), _) | (Err(error::Error::WifiStationRequestChannelClosed), _) => {
+                    return resp
+                }
+                (_, None) => return resp,
+                (Err(e), Some(policy)) => {
+                    warn!("Lost wpa_supplicant control socket: {e}");
+                    policy
+                }
+            };
+            match self.reopen(policy).await? {
+                Some(reopened) => sockets = reopened,
+                // shutdown was requested while reconnecting
+                None => return Ok(()),
+            }
+            self.disconnected.store(false, Ordering::Relaxed);
+            info!("Reconnected to wpa_supplicant control socket");
+        }
+    }
+
+    async fn run_attached(
+        &mut self,
+        socket_handle: SocketHandle<10240>,
+        event_socket_handle: SocketHandle<4096>,
+    ) -> Result {
+        let (unsolicited_receiver, unsolicited) = EventSocket::new(
+            event_socket_handle,
+            self.interface.clone(),
+            self.raw_broadcast_sender.clone(),
+        );
+        self.broadcast_sender.send(Broadcast::Ready)?;
+        // Requests awaiting events outlive the select below, so that the disconnected flag is
+        // raised before they are dropped and their callers learn why
+        let mut pending = PendingRequests::default();
+        let disconnected = self.disconnected.clone();
+        let reconnect = self.reconnect_policy.is_some();
+        let resp = tokio::select!(
+            resp = unsolicited.run() => resp,
+            resp = self.run_internal(unsolicited_receiver, socket_handle, &mut pending) => resp,
+        );
+        if reconnect && resp.is_err() {
+            disconnected.store(true, Ordering::Relaxed);
+        }
+        resp
+    }
+
+    /// Retries opening the control socket with exponential backoff. Requests received in the
+    /// meantime are dropped so that callers fail fast rather than hang.
+    async fn reopen(
+        &mut self,
+        policy: ReconnectPolicy,
+    ) -> Result<Option<(SocketHandle<10240>, SocketHandle<4096>)>> {
+        let mut interval = policy.initial_interval;
+        for attempt in 1..=policy.max_attempts {
+            let sleep = tokio::time::sleep(interval);
+            tokio::pin!(sleep);
+            loop {
                 tokio::select!(
-                    resp = unsolicited.run() => resp,
-                    resp = self.run_internal(unsolicited_receiver, socket_handle) => resp,
+                    _ = &mut sleep => break,
+                    request = self.request_receiver.recv() => match request {
+                        Some(Request::Shutdown(ack)) => {
+                            self.shutdown_ack = Some(ack);
+                            return Ok(None);
+                        }
+                        Some(request) => debug!("Control socket disconnected, dropping request: {request:?}"),
+                        None => return Err(error::Error::WifiStationRequestChannelClosed),
+                    },
                 )
-            } => resp,
-        )
+            }
+            let reopened = SocketHandle::try_open(&self.socket_path, SYNC_SOCKET_LABEL).and_then(
+                |socket_handle| {
+                    let event_socket_handle =
+                        SocketHandle::try_open(&self.socket_path, ASYNC_SOCKET_LABEL)?;
+                    Ok((socket_handle, event_socket_handle))
+                },
+            );
+            match reopened {
+                Ok(sockets) => return Ok(Some(sockets)),
+                Err(e) => info!(
+                    "Reconnection attempt {attempt}/{} failed: {e}",
+                    policy.max_attempts
+                ),
+            }
+            interval = (interval * 2).min(policy.max_interval);
+        }
+        Err(error::Error::ControlSocketDisconnected)
     }
 
     async fn run_internal(
-        mut self,
+        &mut self,
         mut unsolicited_receiver: EventReceiver,
         mut socket_handle: SocketHandle<10240>,
+        pending: &mut PendingRequests,
     ) -> Result {
-        // We will collect scan requests and batch respond to them when results are ready
-        let mut scan_requests = Vec::new();
-        let mut select_request = None;
+        // When reconnection is enabled, we ping the supplicant regularly since a restart
+        // of wpa_supplicant otherwise goes unnoticed on the event socket
+        let keepalive_enabled = self.reconnect_policy.is_some();
+        let mut keepalive = tokio::time::interval(
+            self.reconnect_policy
+                .map(|policy| policy.keepalive_interval)
+                .unwrap_or(tokio::time::Duration::from_secs(60)),
+        );
+        // unknown until the next connection when the runtime starts or reattaches
+        let mut connected_since = None;
+        // the progress of connecting is derived from events, starting from the current state
+        let mut wpa_state = match socket_handle.request(b"STATUS").await {
+            Ok(status) => Status::from_response(&status)
+                .map(|status| status.wpa_state)
+                .unwrap_or(WpaState::Disconnected),
+            Err(e) => {
+                debug!("Error while reading initial state: {e}");
+                WpaState::Disconnected
+            }
+        };
         loop {
             enum EventOrRequest {
                 Event(Option<Event>),
                 Request(Option<Request>),
+                Keepalive,
+                ScanCancelled,
             }
 
             let event_or_request = tokio::select!(
@@ -66,36 +237,94 @@ impl WifiStation {
                 request = self.request_receiver.recv() => {
                     EventOrRequest::Request(request)
                 },
+                _ = keepalive.tick(), if keepalive_enabled => EventOrRequest::Keepalive,
+                _ = all_closed(&mut pending.scan_requests), if !pending.scan_requests.is_empty() => {
+                    EventOrRequest::ScanCancelled
+                },
             );
 
             match event_or_request {
                 EventOrRequest::Event(event) => match event {
                     Some(unsolicited_msg) => {
                         debug!("Unsolicited event: {unsolicited_msg:?}");
+                        match &unsolicited_msg {
+                            // roaming within the network keeps the connection time
+                            Event::Connected => {
+                                connected_since.get_or_insert_with(std::time::Instant::now);
+                            }
+                            Event::Disconnected(_) => connected_since = None,
+                            _ => (),
+                        }
+                        let new_state = match &unsolicited_msg {
+                            Event::StateChange(state) => Some(state.clone()),
+                            // background scans don't set back an established connection
+                            Event::ScanStarted if wpa_state.connection_progress() == 0 => {
+                                Some(WpaState::Scanning)
+                            }
+                            Event::Connected => Some(WpaState::Completed),
+                            Event::Disconnected(_)
+                            | Event::AssocReject { .. }
+                            | Event::NetworkNotFound
+                            | Event::NetworkTempDisabled { .. } => Some(WpaState::Disconnected),
+                            _ => None,
+                        };
                         Self::handle_event(
                             &mut socket_handle,
                             unsolicited_msg,
-                            &mut scan_requests,
-                            &mut select_request,
+                            pending,
                             &mut self.broadcast_sender,
+                            &self.p2p_broadcast_sender,
                         )
-                        .await?
+                        .await?;
+                        if let Some(new_state) = new_state {
+                            let progress = new_state.connection_progress();
+                            if progress != wpa_state.connection_progress()
+                                && self
+                                    .broadcast_sender
+                                    .send(Broadcast::ConnectionProgress(progress))
+                                    .is_err()
+                            {
+                                debug!("No receiver for connection progress broadcast");
+                            }
+                            wpa_state = new_state;
+                        }
                     }
                     None => return Err(error::Error::WifiStationEventChannelClosed),
                 },
                 EventOrRequest::Request(request) => match request {
-                    Some(Request::Shutdown) => return Ok(()),
+                    Some(Request::Shutdown(ack)) => {
+                        self.shutdown_ack = Some(ack);
+                        return Ok(());
+                    }
+                    Some(Request::ConnectedSince(response_channel)) => {
+                        if response_channel.send(connected_since).is_err() {
+                            error!("Connected since response channel closed before response sent");
+                        }
+                    }
                     Some(request) => {
                         Self::handle_request(
                             &mut socket_handle,
                             request,
-                            &mut scan_requests,
-                            &mut select_request,
+                            pending,
+                            self.coalesce_scans,
                         )
                         .await?;
                     }
                     None => return Err(error::Error::WifiStationRequestChannelClosed),
                 },
+                EventOrRequest::ScanCancelled => {
+                    debug!("Every scan request was dropped, aborting the scan");
+                    pending.scan_requests.clear();
+                    if pending.scan_in_progress {
+                        if let Err(e) = socket_handle.command(b"ABORT_SCAN").await {
+                            debug!("Error while aborting scan: {e}");
+                        }
+                    }
+                }
+                EventOrRequest::Keepalive => match socket_handle.request(b"PING").await {
+                    Ok(pong) if pong == "PONG" => (),
+                    Ok(_) | Err(_) => return Err(error::Error::ControlSocketDisconnected),
+                },
             }
         }
     }
@@ -103,50 +332,126 @@ impl WifiStation {
     async fn handle_event<const N: usize>(
         socket_handle: &mut SocketHandle<N>,
         event: Event,
-        scan_requests: &mut Vec<oneshot::Sender<Arc<Vec<ScanResult>>>>,
-        select_request: &mut Option<oneshot::Sender<SelectResult>>,
+        pending: &mut PendingRequests,
         broadcast_sender: &mut broadcast::Sender<Broadcast>,
+        p2p_broadcast_sender: &broadcast::Sender<P2pEvent>,
     ) -> Result {
+        let PendingRequests {
+            scan_requests,
+            scan_in_progress,
+            select_request,
+            disconnect_requests,
+            wps_request,
+        } = pending;
         match event {
+            Event::ScanStarted => *scan_in_progress = true,
+            // the results cached by wpa_supplicant are still worth something, so callers
+            // get them marked as incomplete
+            Event::ScanFailed(ret) => {
+                *scan_in_progress = false;
+                Self::respond_scan(socket_handle, scan_requests, Some(ret)).await?;
+            }
             Event::ScanComplete => {
-                let _n = socket_handle.socket.send(b"SCAN_RESULTS").await?;
-                let n = socket_handle.socket.recv(&mut socket_handle.buffer).await?;
-                let data_str = std::str::from_utf8(&socket_handle.buffer[..n])?;
-                let mut scan_results = ScanResult::vec_from_str(data_str)?;
-                scan_results.sort_by(|a, b| a.signal.cmp(&b.signal));
-
-                let results = Arc::new(scan_results);
-                while let Some(scan_request) = scan_requests.pop() {
-                    if scan_request.send(results.clone()).is_err() {
-                        error!("Scan request response channel closed before response sent");
-                    }
+                *scan_in_progress = false;
+                Self::respond_scan(socket_handle, scan_requests, None).await?;
+                // background scans complete regularly, so this mustn't fail without listeners
+                if broadcast_sender
+                    .send(Broadcast::ScanResultsAvailable)
+                    .is_err()
+                {
+                    debug!("No receiver for scan results broadcast");
+                }
+            }
+            // DPP events arrive whenever a peer is around, listened for or not
+            Event::Dpp(event) => {
+                if broadcast_sender.send(Broadcast::Dpp(event)).is_err() {
+                    debug!("No receiver for DPP broadcast");
+                }
+            }
+            // unlike station broadcasts, nobody listening for P2P events is not an error
+            Event::P2p(event) => {
+                if p2p_broadcast_sender.send(event).is_err() {
+                    debug!("No receiver for P2P event");
                 }
             }
             Event::Connected => {
                 broadcast_sender.send(Broadcast::Connected)?;
-                if let Some(sender) = select_request.take() {
-                    sender
-                        .send(SelectResult::Success)
-                        .map_err(|_| error::Error::WifiSelect)?;
+                if let Some((_, sender)) = select_request.take() {
+                    if sender.send(SelectResult::Success).is_err() {
+                        error!("Select request response channel closed before response sent");
+                    }
                 }
             }
-            Event::Disconnected => {
-                broadcast_sender.send(Broadcast::Disconnected)?;
+            Event::Disconnected(info) => {
+                broadcast_sender.send(Broadcast::Disconnected(info))?;
+                while let Some(disconnect_request) = disconnect_requests.pop() {
+                    if disconnect_request.send(Ok(())).is_err() {
+                        error!("Disconnect request response channel closed before response sent");
+                    }
+                }
+            }
+            // unlike the original connection broadcasts, failing to send authentication
+            // details mustn't stop the runtime when nobody listens
+            Event::AssocReject { bssid, status_code } => {
+                let broadcast = Broadcast::AssocReject { bssid, status_code };
+                if broadcast_sender.send(broadcast).is_err() {
+                    debug!("No receiver for association rejection broadcast");
+                }
+            }
+            Event::NetworkTempDisabled {
+                network_id,
+                auth_failures,
+                reason,
+            } => {
+                let broadcast = Broadcast::NetworkTempDisabled {
+                    network_id,
+                    auth_failures,
+                    reason,
+                };
+                if broadcast_sender.send(broadcast).is_err() {
+                    debug!("No receiver for temporarily disabled network broadcast");
+                }
             }
             Event::NetworkNotFound => {
                 broadcast_sender.send(Broadcast::NetworkNotFound)?;
-                if let Some(sender) = select_request.take() {
-                    sender
-                        .send(SelectResult::NotFound)
-                        .map_err(|_| error::Error::WifiSelect)?;
+                if let Some((_, sender)) = select_request.take() {
+                    if sender.send(SelectResult::NotFound).is_err() {
+                        error!("Select request response channel closed before response sent");
+                    }
                 }
             }
             Event::WrongPsk => {
                 broadcast_sender.send(Broadcast::WrongPsk)?;
-                if let Some(sender) = select_request.take() {
-                    sender
-                        .send(SelectResult::WrongPsk)
-                        .map_err(|_| error::Error::WifiSelect)?;
+                if let Some((_, sender)) = select_request.take() {
+                    if sender.send(SelectResult::WrongPsk).is_err() {
+                        error!("Select request response channel closed before response sent");
+                    }
+                }
+            }
+            Event::EapStarted | Event::EapSuccess | Event::EapFailure | Event::HandshakeFailed => {
+                let broadcast = match event {
+                    Event::EapStarted => Broadcast::EapStarted,
+                    Event::EapSuccess => Broadcast::EapSuccess,
+                    Event::EapFailure => Broadcast::EapFailure,
+                    _ => Broadcast::HandshakeFailed,
+                };
+                if broadcast_sender.send(broadcast).is_err() {
+                    debug!("No receiver for authentication broadcast");
+                }
+            }
+            // broadcast as progress by the caller, which tracks the state
+            Event::StateChange(_) => (),
+            Event::WpsSuccess | Event::WpsTimeout | Event::WpsOverlap | Event::WpsFail => {
+                let result = match event {
+                    Event::WpsSuccess => WpsResult::Success,
+                    Event::WpsTimeout => WpsResult::Timeout,
+                    Event::WpsOverlap => WpsResult::Overlap,
+                    _ => WpsResult::Failed,
+                };
+                if let Some(sender) = wps_request.take() {
+                    if sender.send(Ok(result)).is_err() {
+                        error!("WPS request response channel closed before response sent");
+                    }
                 }
             }
         }
@@ -156,15 +461,66 @@ impl WifiStation {
     async fn handle_request<const N: usize>(
         socket_handle: &mut SocketHandle<N>,
         request: Request,
-        scan_requests: &mut Vec<oneshot::Sender<Arc<Vec<ScanResult>>>>,
-        select_request: &mut Option<oneshot::Sender<SelectResult>>,
+        pending: &mut PendingRequests,
+        coalesce_scans: bool,
     ) -> Result {
+        let PendingRequests {
+            scan_requests,
+            scan_in_progress,
+            select_request,
+            disconnect_requests,
+            wps_request,
+        } = pending;
         debug!("Handling request: {request:?}");
+        // forget a pending selection whose caller stopped waiting so it can't block new ones
+        if select_request.as_ref().is_some_and(|(_, s)| s.is_closed()) {
+            *select_request = None;
+        }
+        if wps_request.as_ref().is_some_and(|s| s.is_closed()) {
+            *wps_request = None;
+        }
+        disconnect_requests.retain(|s| !s.is_closed());
         match request {
-            Request::Scan(response_channel) => {
+            // the results of the scan in flight are good enough unless the caller
+            // asked for specific SSIDs or frequencies
+            Request::Scan(params, response_channel)
+                if coalesce_scans && *scan_in_progress && params.is_default() =>
+            {
+                debug!("Scan already in progress, awaiting its results");
                 scan_requests.push(response_channel);
-                if let Err(e) = socket_handle.command(b"SCAN").await {
-                    debug!("Error while requesting SCAN: {e}");
+            }
+            Request::Scan(params, response_channel) => {
+                if params.flush {
+                    if let Err(e) = socket_handle.command(b"BSS_FLUSH 0").await {
+                        warn!("Error while flushing BSS entries: {e}");
+                    }
+                }
+                let cmd = params.command();
+                match socket_handle.request(cmd.as_bytes()).await {
+                    Ok(resp) if resp == "OK" => {
+                        *scan_in_progress = true;
+                        scan_requests.push(response_channel);
+                    }
+                    // a scan we haven't been told about yet, eg: started by wpa_supplicant
+                    Ok(resp) if resp == "FAIL-BUSY" && coalesce_scans && params.is_default() => {
+                        debug!("Scan already in progress, awaiting its results");
+                        scan_requests.push(response_channel);
+                    }
+                    resp => {
+                        let resp = match resp {
+                            Ok(resp) if resp == "FAIL-BUSY" => Err(error::Error::ScanBusy),
+                            Ok(resp) => Err(error::Error::UnexpectedWifiApRepsonse(resp)),
+                            Err(e) => Err(e),
+                        };
+                        if response_channel.send(resp).is_err() {
+                            error!("Scan request response channel closed before response sent");
+                        }
+                    }
+                }
+            }
+            Request::ScanInProgress(response_channel) => {
+                if response_channel.send(*scan_in_progress).is_err() {
+                    error!("ScanInProgress request response channel closed before response sent");
                 }
             }
             Request::Networks(response_channel) => {
@@ -172,7 +528,7 @@ impl WifiStation {
                 let n = socket_handle.socket.recv(&mut socket_handle.buffer).await?;
                 let data_str = std::str::from_utf8(&socket_handle.buffer[..n])?.trim_end();
                 let network_list =
-                    NetworkResult::vec_from_str(data_str, &mut socket_handle.socket).await?;
+                    NetworkResult::vec_from_str(data_str, &mut socket_handle.socket).await;
                 if response_channel.send(network_list).is_err() {
                     error!("Scan request response channel closed before response sent");
                 }
@@ -181,11 +537,48 @@ impl WifiStation {
                 let _n = socket_handle.socket.send(b"STATUS").await?;
                 let n = socket_handle.socket.recv(&mut socket_handle.buffer).await?;
                 let data_str = std::str::from_utf8(&socket_handle.buffer[..n])?.trim_end();
-                let status = parse_status(data_str);
+                let status = Status::from_response(data_str);
                 if response_channel.send(status).is_err() {
                     error!("Scan request response channel closed before response sent");
                 }
             }
+            Request::SignalPoll(response_channel) => {
+                let signal_poll = socket_handle
+                    .query(b"SIGNAL_POLL")
+                    .await
+                    .and_then(|data_str| SignalPoll::from_response(&data_str));
+                if response_channel.send(signal_poll).is_err() {
+                    error!("Signal poll response channel closed before response sent");
+                }
+            }
+            Request::PktcntPoll(response_channel) => {
+                let stats = socket_handle
+                    .query(b"PKTCNT_POLL")
+                    .await
+                    .and_then(|data_str| LinkStats::from_response(&data_str));
+                if response_channel.send(stats).is_err() {
+                    error!("Packet count poll response channel closed before response sent");
+                }
+            }
+            Request::Mib(response_channel) => {
+                let mib = socket_handle
+                    .query(b"MIB")
+                    .await
+                    .map(|data_str| Mib::from_response(&data_str));
+                if response_channel.send(mib).is_err() {
+                    error!("MIB response channel closed before response sent");
+                }
+            }
+            Request::Bss(selector, response_channel) => {
+                let cmd = format!("BSS {selector}");
+                let bss = socket_handle
+                    .query(cmd.as_bytes())
+                    .await
+                    .and_then(|data_str| BssInfo::from_response(&data_str));
+                if response_channel.send(bss).is_err() {
+                    error!("BSS request response channel closed before response sent");
+                }
+            }
             Request::AddNetwork(response_channel) => {
                 let _n = socket_handle.socket.send(b"ADD_NETWORK").await?;
                 let n = socket_handle.socket.recv(&mut socket_handle.buffer).await?;
@@ -197,27 +590,60 @@ impl WifiStation {
                     debug!("wpa_ctrl created network {network_id}");
                 }
             }
-            Request::SetNetwork(id, param) => {
-                let cmd = format!(
-                    "SET_NETWORK {id} {}",
-                    match param {
-                        SetNetwork::Ssid(ssid) => format!("ssid \"{ssid}\""),
-                        SetNetwork::Psk(psk) => format!("psk \"{psk}\""),
-                    }
-                );
+            Request::SetNetwork(id, param, response_channel) => {
+                let cmd = param.command(id);
                 debug!("wpa_ctrl \"{cmd}\"");
                 let bytes = cmd.into_bytes();
-                if let Err(e) = socket_handle.command(&bytes).await {
+                let resp = socket_handle.command(&bytes).await;
+                if let Err(e) = &resp {
                     warn!("Error while setting network parameter: {e}");
                 }
+                if response_channel.send(resp).is_err() {
+                    error!("Set network response channel closed before response sent");
+                }
+            }
+            Request::AddConfiguredNetwork(params, disabled, response_channel) => {
+                let resp = Self::add_configured_network(socket_handle, params, disabled).await;
+                if let Err(e) = &resp {
+                    warn!("Error while adding configured network: {e}");
+                }
+                if response_channel.send(resp).is_err() {
+                    error!("Add network response channel closed before response sent");
+                }
+            }
+            Request::GetNetwork(id, key, response_channel) => {
+                let cmd = format!("GET_NETWORK {id} {key}");
+                let resp = socket_handle.query(cmd.as_bytes()).await;
+                if response_channel.send(resp).is_err() {
+                    error!("Get network response channel closed before response sent");
+                }
             }
-            Request::SaveConfig => {
-                if let Err(e) = socket_handle.command(b"SAVE_CONFIG").await {
+            Request::SaveConfig(response_channel) => {
+                let resp = match socket_handle.request(b"SAVE_CONFIG").await {
+                    Ok(resp) if resp == "OK" => {
+                        debug!("wpa_ctrl config saved");
+                        Ok(())
+                    }
+                    // wpa_supplicant refuses to save unless update_config=1
+                    Ok(resp) if resp.starts_with("FAIL") => Err(error::Error::SaveConfigDisabled),
+                    Ok(resp) => Err(error::Error::UnexpectedWifiApRepsonse(resp)),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = &resp {
                     warn!("Error while saving config: {e}");
                 }
-                debug!("wpa_ctrl config saved");
+                if response_channel.send(resp).is_err() {
+                    error!("Save config response channel closed before response sent");
+                }
             }
             Request::RemoveNetwork(id) => {
+                // a selection of the network, eg: one timed out by `connect`, is over
+                if select_request
+                    .as_ref()
+                    .is_some_and(|(pending, _)| *pending == id)
+                {
+                    *select_request = None;
+                }
                 let cmd = format!("REMOVE_NETWORK {id}");
                 let bytes = cmd.into_bytes();
                 if let Err(e) = socket_handle.command(&bytes).await {
@@ -225,36 +651,410 @@ impl WifiStation {
                 }
                 debug!("wpa_ctrl removed network {id}");
             }
+            Request::RemoveAllNetworks(response_channel) => {
+                let resp = match socket_handle.query(b"LIST_NETWORKS").await {
+                    // the first line is the header
+                    Ok(list) => {
+                        let count = list.lines().skip(1).filter(|l| !l.is_empty()).count();
+                        socket_handle
+                            .command(b"REMOVE_NETWORK all")
+                            .await
+                            .map(|()| count)
+                    }
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = &resp {
+                    warn!("Error while removing all networks: {e}");
+                }
+                if response_channel.send(resp).is_err() {
+                    error!(
+                        "RemoveAllNetworks request response channel closed before response sent"
+                    );
+                }
+            }
+            Request::EnableNetwork(id) => {
+                let cmd = format!("ENABLE_NETWORK {id}");
+                let bytes = cmd.into_bytes();
+                if let Err(e) = socket_handle.command(&bytes).await {
+                    warn!("Error while enabling network {id}: {e}");
+                }
+                debug!("wpa_ctrl enabled network {id}");
+            }
+            Request::DisableNetwork(id) => {
+                let cmd = format!("DISABLE_NETWORK {id}");
+                let bytes = cmd.into_bytes();
+                if let Err(e) = socket_handle.command(&bytes).await {
+                    warn!("Error while disabling network {id}: {e}");
+                }
+                debug!("wpa_ctrl disabled network {id}");
+            }
             Request::SelectNetwork(id, response_sender) => {
-                let response_sender = match select_request {
-                    None => {
-                        let cmd = format!("SELECT_NETWORK {id}");
-                        let bytes = cmd.into_bytes();
-                        if let Err(e) = socket_handle.command(&bytes).await {
-                            warn!("Error while selecting network {id}: {e}");
-                            response_sender
-                                .send(SelectResult::InvalidNetworkId)
-                                .map_err(|_| error::Error::WifiSelect)?;
-                            None
-                        } else {
-                            debug!("wpa_ctrl selected network {id}");
-                            Some(response_sender)
+                Self::select_network(socket_handle, id, response_sender, select_request).await?;
+            }
+            Request::SelectBssid(id, bssid, response_sender) => {
+                if let Some((network_id, _)) = select_request {
+                    warn!("Select request already pending! Dropping this one.");
+                    let pending = SelectResult::PendingSelect {
+                        network_id: *network_id,
+                    };
+                    if response_sender.send(pending).is_err() {
+                        error!("Select request response channel closed before response sent");
+                    }
+                    return Ok(());
+                }
+                // an all-zero BSSID clears the pin, so there is nothing to look for
+                if bssid != Bssid::ANY && !Self::in_scan_results(socket_handle, bssid).await? {
+                    warn!("BSSID {bssid} not found in scan results");
+                    if response_sender.send(SelectResult::BssidNotFound).is_err() {
+                        error!("Select request response channel closed before response sent");
+                    }
+                    return Ok(());
+                }
+                let cmd = format!("BSSID {id} {bssid}");
+                if let Err(e) = socket_handle.command(cmd.as_bytes()).await {
+                    warn!("Error while setting BSSID {bssid} on network {id}: {e}");
+                    if response_sender
+                        .send(SelectResult::InvalidNetworkId)
+                        .is_err()
+                    {
+                        error!("Select request response channel closed before response sent");
+                    }
+                    return Ok(());
+                }
+                debug!("wpa_ctrl pinned network {id} to {bssid}");
+                Self::select_network(socket_handle, id, response_sender, select_request).await?;
+            }
+            Request::Roam(bssid, response_channel) => {
+                let resp = if Self::in_scan_results(socket_handle, bssid).await? {
+                    socket_handle
+                        .command(format!("ROAM {bssid}").as_bytes())
+                        .await
+                } else {
+                    Err(error::Error::BssidNotFound(bssid))
+                };
+                if let Err(e) = &resp {
+                    warn!("Error while roaming to {bssid}: {e}");
+                }
+                if response_channel.send(resp).is_err() {
+                    error!("Roam request response channel closed before response sent");
+                }
+            }
+            Request::Disconnect(response_channel) => {
+                // DISCONNECT only yields a CTRL-EVENT-DISCONNECTED if we are associated,
+                // so check the state first and answer right away when we are not
+                let _n = socket_handle.socket.send(b"STATUS").await?;
+                let n = socket_handle.socket.recv(&mut socket_handle.buffer).await?;
+                let data_str = std::str::from_utf8(&socket_handle.buffer[..n])?.trim_end();
+                let associated = Status::from_response(data_str)
+                    .map(|status| {
+                        matches!(
+                            status.wpa_state,
+                            WpaState::Associated
+                                | WpaState::FourWayHandshake
+                                | WpaState::GroupHandshake
+                                | WpaState::Completed
+                        )
+                    })
+                    .unwrap_or(false);
+                match socket_handle.command(b"DISCONNECT").await {
+                    Ok(()) if associated => {
+                        debug!("wpa_ctrl disconnecting");
+                        disconnect_requests.push(response_channel);
+                    }
+                    resp => {
+                        if let Err(e) = &resp {
+                            warn!("Error while disconnecting: {e}");
+                        }
+                        if response_channel.send(resp).is_err() {
+                            error!(
+                                "Disconnect request response channel closed before response sent"
+                            );
                         }
                     }
-                    Some(_) => {
-                        warn!("Select request already pending! Dropping this one.");
-                        response_sender
-                            .send(SelectResult::PendingSelect)
-                            .map_err(|_| error::Error::WifiSelect)?;
-                        debug!("wpa_ctrl removed network {id}");
-                        None
+                }
+            }
+            Request::Reconnect(response_channel) => {
+                let resp = socket_handle.command(b"RECONNECT").await;
+                if let Err(e) = &resp {
+                    warn!("Error while reconnecting: {e}");
+                }
+                if response_channel.send(resp).is_err() {
+                    error!("Reconnect request response channel closed before response sent");
+                }
+            }
+            Request::Ping(response_channel) => {
+                let resp = match socket_handle.request(b"PING").await {
+                    Ok(pong) if pong == "PONG" => Ok(()),
+                    Ok(_) | Err(_) => Err(error::Error::PingFailed),
+                };
+                if response_channel.send(resp).is_err() {
+                    error!("Ping request response channel closed before response sent");
+                }
+            }
+            Request::Reassociate(response_channel) => {
+                let resp = socket_handle.command(b"REASSOCIATE").await;
+                if let Err(e) = &resp {
+                    warn!("Error while reassociating: {e}");
+                }
+                if response_channel.send(resp).is_err() {
+                    error!("Reassociate request response channel closed before response sent");
+                }
+            }
+            Request::Reconfigure(response_channel) => {
+                let resp = socket_handle.command(b"RECONFIGURE").await;
+                if let Err(e) = &resp {
+                    warn!("Error while reconfiguring: {e}");
+                }
+                if response_channel.send(resp).is_err() {
+                    error!("Reconfigure request response channel closed before response sent");
+                }
+            }
+            // starting another exchange would silently replace the one awaited
+            Request::WpsPbc(response_channel) if wps_request.is_some() => {
+                if response_channel
+                    .send(Err(error::Error::WpsInProgress))
+                    .is_err()
+                {
+                    error!("WPS request response channel closed before response sent");
+                }
+            }
+            Request::WpsPbc(response_channel) => {
+                if let Err(e) = socket_handle.command(b"WPS_PBC").await {
+                    warn!("Error while starting WPS PBC: {e}");
+                    if response_channel.send(Ok(WpsResult::Failed)).is_err() {
+                        error!("WPS request response channel closed before response sent");
                     }
+                } else {
+                    debug!("wpa_ctrl started WPS PBC");
+                    *wps_request = Some(response_channel);
+                }
+            }
+            Request::WpsPin(pin, response_channel) => {
+                let cmd = match pin {
+                    Some(pin) => format!("WPS_PIN any {pin}"),
+                    None => "WPS_PIN any".to_string(),
                 };
-                if let Some(response_sender) = response_sender {
-                    *select_request = Some(response_sender);
+                let resp = socket_handle.query(cmd.as_bytes()).await;
+                match &resp {
+                    Ok(pin) => debug!("wpa_ctrl started WPS PIN {pin}"),
+                    Err(e) => warn!("Error while starting WPS PIN: {e}"),
                 }
+                if response_channel.send(resp).is_err() {
+                    error!("WPS request response channel closed before response sent");
+                }
+            }
+            Request::P2pPeers(response_channel) => {
+                let resp = Self::p2p_peers(socket_handle).await;
+                if response_channel.send(resp).is_err() {
+                    error!("P2P peers response channel closed before response sent");
+                }
+            }
+            Request::Raw(cmd, response_channel) => {
+                debug!("wpa_ctrl \"{cmd}\"");
+                let resp = socket_handle.request(cmd.as_bytes()).await;
+                if response_channel.send(resp).is_err() {
+                    error!("Raw command response channel closed before response sent");
+                }
+            }
+            Request::Set(key, value, response_channel) => {
+                let cmd = format!("SET {key} {value}");
+                debug!("wpa_ctrl \"{cmd}\"");
+                let resp = socket_handle.command(cmd.as_bytes()).await;
+                if let Err(e) = &resp {
+                    warn!("Error while setting {key}: {e}");
+                }
+                if response_channel.send(resp).is_err() {
+                    error!("Set request response channel closed before response sent");
+                }
+            }
+            Request::Get(key, response_channel) => {
+                let cmd = format!("GET {key}");
+                let resp = socket_handle.query(cmd.as_bytes()).await;
+                if response_channel.send(resp).is_err() {
+                    error!("Get request response channel closed before response sent");
+                }
+            }
+            // handled at the scope above
+            Request::Shutdown(_) | Request::ConnectedSince(_) => (),
+        }
+        Ok(())
+    }
+
+    /// Adds a network and sets all its parameters, removing it again if any of them fails
+    async fn add_configured_network<const N: usize>(
+        socket_handle: &mut SocketHandle<N>,
+        params: Vec<SetNetwork>,
+        disabled: bool,
+    ) -> Result<usize> {
+        let id = socket_handle.query(b"ADD_NETWORK").await?;
+        let id = error::parse_value("ADD_NETWORK", &id, &id)?;
+        let disable = disabled.then(|| format!("DISABLE_NETWORK {id}"));
+        let params = params.into_iter().map(|param| param.command(id));
+        for cmd in disable.into_iter().chain(params) {
+            debug!("wpa_ctrl \"{cmd}\"");
+            if let Err(e) = socket_handle.command(cmd.as_bytes()).await {
+                let cmd = format!("REMOVE_NETWORK {id}");
+                if let Err(e) = socket_handle.command(cmd.as_bytes()).await {
+                    warn!("Error while removing network {id}: {e}");
+                }
+                return Err(e);
+            }
+        }
+        debug!("wpa_ctrl created network {id}");
+        Ok(id)
+    }
+
+    async fn in_scan_results<const N: usize>(
+        socket_handle: &mut SocketHandle<N>,
+        bssid: Bssid,
+    ) -> Result<bool> {
+        let scan_results = socket_handle.query(b"SCAN_RESULTS").await?;
+        let scan_results = match ScanResult::vec_from_str(&scan_results) {
+            Ok(scan_results) => scan_results,
+            Err(e) => {
+                warn!("Error parsing scan results: {e}");
+                Vec::new()
+            }
+        };
+        Ok(scan_results.iter().any(|r| r.mac == bssid))
+    }
+
+    /// Reads the scan results and answers every pending scan request with them, marked as
+    /// incomplete when `failure` carries the failed scan's `ret=` code, if any
+    async fn respond_scan<const N: usize>(
+        socket_handle: &mut SocketHandle<N>,
+        scan_requests: &mut Vec<oneshot::Sender<Result<ScanOutcome>>>,
+        failure: Option<Option<i32>>,
+    ) -> Result {
+        // background scans complete without anybody waiting for their results
+        if scan_requests.is_empty() {
+            return Ok(());
+        }
+        let _n = socket_handle.socket.send(b"SCAN_RESULTS").await?;
+        let n = socket_handle.socket.recv(&mut socket_handle.buffer).await?;
+        let data_str = std::str::from_utf8(&socket_handle.buffer[..n])?;
+        let results = match ScanResult::vec_from_str(data_str) {
+            Ok(mut scan_results) => {
+                Self::query_scan_ages(socket_handle, &mut scan_results).await;
+                scan_results.sort_by_key(|a| a.signal);
+                Ok(Arc::new(scan_results))
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = &results {
+            warn!("Error parsing scan results: {e}");
+        }
+
+        while let Some(scan_request) = scan_requests.pop() {
+            let resp = match &results {
+                Ok(results) => Ok(ScanOutcome {
+                    results: results.clone(),
+                    complete: failure.is_none(),
+                    failure_code: failure.flatten(),
+                }),
+                Err(error::Error::Parse {
+                    command,
+                    line,
+                    reason,
+                }) => Err(error::Error::Parse {
+                    command,
+                    line: line.clone(),
+                    reason: reason.clone(),
+                }),
+                Err(e) => Err(error::Error::UnexpectedWifiApRepsonse(e.to_string())),
+            };
+            if scan_request.send(resp).is_err() {
+                error!("Scan request response channel closed before response sent");
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the discovered P2P peers, querying the details of each. Peers lost in the
+    /// meantime are skipped.
+    async fn p2p_peers<const N: usize>(
+        socket_handle: &mut SocketHandle<N>,
+    ) -> Result<Vec<P2pPeer>> {
+        let addresses = socket_handle.query(b"P2P_PEERS").await?;
+        let mut peers = Vec::new();
+        for address in addresses.lines().filter(|line| !line.is_empty()) {
+            let cmd = format!("P2P_PEER {address}");
+            match socket_handle.query(cmd.as_bytes()).await {
+                Ok(resp) => peers.push(P2pPeer::from_response(&resp)?),
+                Err(e) => debug!("Error querying P2P peer {address}: {e}"),
+            }
+        }
+        Ok(peers)
+    }
+
+    /// `SCAN_RESULTS` doesn't report ages, so they are read with a single `BSS RANGE=ALL`,
+    /// masked down to the BSSID and age of each entry. Entries that didn't fit in the
+    /// response are left without an age.
+    async fn query_scan_ages<const N: usize>(
+        socket_handle: &mut SocketHandle<N>,
+        scan_results: &mut [ScanResult],
+    ) {
+        // WPA_BSS_MASK_BSSID | WPA_BSS_MASK_AGE | WPA_BSS_MASK_DELIM
+        let data_str = match socket_handle.query(b"BSS RANGE=ALL MASK=0x20202").await {
+            Ok(data_str) => data_str,
+            Err(e) => {
+                debug!("Error querying BSS ages: {e}");
+                return;
+            }
+        };
+        let mut ages = std::collections::HashMap::new();
+        for entry in data_str.split("====") {
+            match BssInfo::from_response(entry) {
+                Ok(Some(bss)) => {
+                    ages.insert(bss.bssid, bss.age);
+                }
+                Ok(None) => (),
+                Err(e) => debug!("Error parsing BSS age: {e}"),
+            }
+        }
+        for scan_result in scan_results {
+            scan_result.age = ages.get(&scan_result.mac).copied();
+        }
+    }
+
+    async fn select_network<const N: usize>(
+        socket_handle: &mut SocketHandle<N>,
+        id: usize,
+        response_sender: oneshot::Sender<SelectResult>,
+        select_request: &mut Option<(usize, oneshot::Sender<SelectResult>)>,
+    ) -> Result {
+        let response_sender = match select_request {
+            None => {
+                let cmd = format!("SELECT_NETWORK {id}");
+                let bytes = cmd.into_bytes();
+                if let Err(e) = socket_handle.command(&bytes).await {
+                    warn!("Error while selecting network {id}: {e}");
+                    if response_sender
+                        .send(SelectResult::InvalidNetworkId)
+                        .is_err()
+                    {
+                        error!("Select request response channel closed before response sent");
+                    }
+                    None
+                } else {
+                    debug!("wpa_ctrl selected network {id}");
+                    Some(response_sender)
+                }
+            }
+            Some((network_id, _)) => {
+                warn!("Select request already pending! Dropping this one.");
+                let pending = SelectResult::PendingSelect {
+                    network_id: *network_id,
+                };
+                if response_sender.send(pending).is_err() {
+                    error!("Select request response channel closed before response sent");
+                }
+                debug!("wpa_ctrl removed network {id}");
+                None
             }
-            Request::Shutdown => (), //shutdown is handled at the scope above
+        };
+        if let Some(response_sender) = response_sender {
+            *select_request = Some((id, response_sender));
         }
         Ok(())
     }
@@ -0,0 +1,191 @@
+use super::*;
+use crate::error::parse_value;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Finds the value of a `key=value` event parameter, with the value possibly quoted as
+/// `key='..'` or `key=".."` when it may contain spaces, eg: device names and SSIDs
+fn quoted_param<'a>(data_str: &'a str, key: &str) -> Option<&'a str> {
+    let pattern = format!(" {key}=");
+    let start = data_str.find(&pattern)? + pattern.len();
+    let value = &data_str[start..];
+    match value.chars().next() {
+        Some(quote @ ('\'' | '"')) => value[1..].split(quote).next(),
+        _ => value.split_whitespace().next(),
+    }
+}
+
+/// Role of the device in a P2P group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum P2pRole {
+    GroupOwner,
+    Client,
+}
+
+/// P2P (Wi-Fi Direct) events, broadcast on their own channel, see
+/// `WifiSetup::get_p2p_broadcast_receiver`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum P2pEvent {
+    /// A peer was discovered by `p2p_find`
+    DeviceFound {
+        address: Bssid,
+        name: String,
+    },
+    DeviceLost(Bssid),
+    /// A group was formed, operating on the network interface `ifname`
+    GroupStarted {
+        ifname: String,
+        role: P2pRole,
+        ssid: String,
+        /// Operating frequency in MHz
+        freq: Option<u32>,
+        go_address: Option<Bssid>,
+    },
+    GroupRemoved {
+        ifname: String,
+    },
+}
+
+impl P2pEvent {
+    /// Parses a P2P event line, `None` if it isn't one of the supported events
+    pub(crate) fn from_event(data_str: &str) -> Option<P2pEvent> {
+        let bssid = |key| quoted_param(data_str, key).and_then(|addr| Bssid::from_str(addr).ok());
+        // the group events start with the interface name and role
+        let group = |event: &str| {
+            let start = data_str.find(event)? + event.len();
+            let mut words = data_str[start..].split_whitespace();
+            Some((words.next()?.to_string(), words.next()?))
+        };
+        if data_str.contains("P2P-DEVICE-FOUND ") {
+            Some(P2pEvent::DeviceFound {
+                address: bssid("p2p_dev_addr")?,
+                name: quoted_param(data_str, "name")
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+        } else if data_str.contains("P2P-DEVICE-LOST ") {
+            Some(P2pEvent::DeviceLost(bssid("p2p_dev_addr")?))
+        } else if data_str.contains("P2P-GROUP-STARTED ") {
+            let (ifname, role) = group("P2P-GROUP-STARTED ")?;
+            Some(P2pEvent::GroupStarted {
+                ifname,
+                role: match role {
+                    "GO" => P2pRole::GroupOwner,
+                    _ => P2pRole::Client,
+                },
+                ssid: quoted_param(data_str, "ssid")
+                    .unwrap_or_default()
+                    .to_string(),
+                freq: quoted_param(data_str, "freq").and_then(|freq| freq.parse().ok()),
+                go_address: bssid("go_dev_addr"),
+            })
+        } else if data_str.contains("P2P-GROUP-REMOVED ") {
+            let (ifname, _) = group("P2P-GROUP-REMOVED ")?;
+            Some(P2pEvent::GroupRemoved { ifname })
+        } else {
+            None
+        }
+    }
+}
+
+/// Channel for P2P events, which are kept apart from the station broadcasts
+pub type P2pBroadcastReceiver = broadcast::Receiver<P2pEvent>;
+
+/// A peer discovered by `p2p_find`, as reported by `P2P_PEER`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct P2pPeer {
+    pub address: Bssid,
+    pub device_name: String,
+    /// Every other field reported by wpa_supplicant
+    pub extra: HashMap<String, String>,
+}
+
+impl P2pPeer {
+    pub fn from_response(response: &str) -> Result<P2pPeer> {
+        let mut lines = response.lines();
+        let address = lines.next().unwrap_or_default();
+        let address = parse_value("P2P_PEER", address, address)?;
+        let mut extra: HashMap<String, String> = lines
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        Ok(P2pPeer {
+            address,
+            device_name: extra.remove("device_name").unwrap_or_default(),
+            extra,
+        })
+    }
+}
+
+/// WPS provisioning method used to join a P2P group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum P2pMethod {
+    /// Push button on both devices
+    Pbc,
+    /// A PIN generated here, returned by `p2p_connect`, is entered on the peer
+    DisplayPin,
+    /// The PIN displayed by the peer is entered here
+    KeypadPin(String),
+}
+
+impl fmt::Display for P2pMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            P2pMethod::Pbc => write!(f, "pbc"),
+            P2pMethod::DisplayPin => write!(f, "pin display"),
+            P2pMethod::KeypadPin(pin) => write!(f, "{pin} keypad"),
+        }
+    }
+}
+
+impl RequestClient {
+    /// Starts discovering P2P peers for `timeout`, reporting them as `P2pEvent::DeviceFound`.
+    /// Without a timeout, discovery runs until `p2p_stop_find`. P2P requires wpa_supplicant
+    /// to be built with `CONFIG_P2P` and a driver supporting it.
+    pub async fn p2p_find(&self, timeout: Option<tokio::time::Duration>) -> Result {
+        let cmd = match timeout {
+            Some(timeout) => format!("P2P_FIND {}", timeout.as_secs().max(1)),
+            None => "P2P_FIND".to_string(),
+        };
+        self.raw_checked(cmd).await.map(|_| ())
+    }
+
+    pub async fn p2p_stop_find(&self) -> Result {
+        self.raw_checked("P2P_STOP_FIND".to_string())
+            .await
+            .map(|_| ())
+    }
+
+    /// Peers discovered so far
+    pub async fn p2p_peers(&self) -> Result<Vec<P2pPeer>> {
+        let (response, request) = oneshot::channel();
+        self.send_request(Request::P2pPeers(response)).await?;
+        self.await_response(request).await?
+    }
+
+    /// Starts forming a group with the peer, returning the generated PIN for
+    /// `P2pMethod::DisplayPin`. `P2pEvent::GroupStarted` follows once the group is up.
+    pub async fn p2p_connect<B: Into<Bssid>>(
+        &self,
+        peer: B,
+        method: P2pMethod,
+    ) -> Result<Option<String>> {
+        if let P2pMethod::KeypadPin(pin) = &method {
+            if !pin.bytes().all(|b| b.is_ascii_digit()) || !matches!(pin.len(), 4 | 8) {
+                return Err(error::Error::InvalidWpsPin(pin.clone()));
+            }
+        }
+        let resp = self
+            .raw_checked(format!("P2P_CONNECT {} {method}", peer.into()))
+            .await?;
+        match method {
+            P2pMethod::DisplayPin => Ok(Some(resp)),
+            _ => Ok(None),
+        }
+    }
+}
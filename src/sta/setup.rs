@@ -1,4 +1,30 @@
 use super::*;
+use tokio_stream::StreamExt;
+
+/// How the runtime recovers when the wpa_supplicant control socket breaks, eg: when
+/// wpa_supplicant restarts
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    /// Number of reconnection attempts before the runtime gives up and returns an error
+    pub max_attempts: usize,
+    /// Delay before the first attempt, doubled after every failed attempt
+    pub initial_interval: tokio::time::Duration,
+    /// Upper bound for the delay between attempts
+    pub max_interval: tokio::time::Duration,
+    /// How often the control socket is pinged to detect a restarted wpa_supplicant
+    pub keepalive_interval: tokio::time::Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            initial_interval: tokio::time::Duration::from_millis(500),
+            max_interval: tokio::time::Duration::from_secs(30),
+            keepalive_interval: tokio::time::Duration::from_secs(10),
+        }
+    }
+}
 
 /// A convenient default type for setting up the WiFi Station process.
 pub type WifiSetup = WifiSetupGeneric<32, 32>;
@@ -17,29 +43,109 @@ pub struct WifiSetupGeneric<const C: usize = 32, const B: usize = 32> {
 
 impl<const C: usize, const B: usize> WifiSetupGeneric<C, B> {
     pub fn new() -> Result<Self> {
+        Ok(Self::with_capacities(C, B))
+    }
+
+    fn with_capacities(channel_capacity: usize, broadcast_capacity: usize) -> Self {
         // setup the channel for client requests
-        let (sender, request_receiver) = mpsc::channel(C);
-        let request_client = RequestClient::new(sender);
+        let (sender, request_receiver) = mpsc::channel(channel_capacity);
+        let disconnected = Arc::new(AtomicBool::new(false));
         // setup the channel for broadcasts
-        let (broadcast_sender, broadcast_receiver) = broadcast::channel(B);
+        let (broadcast_sender, broadcast_receiver) = broadcast::channel(broadcast_capacity);
+        let (p2p_broadcast_sender, _) = broadcast::channel(broadcast_capacity);
+        let (raw_broadcast_sender, _) = broadcast::channel(broadcast_capacity);
+        let request_client = RequestClient::new(
+            sender,
+            disconnected.clone(),
+            interface_name(PATH_DEFAULT_SERVER),
+            broadcast_sender.downgrade(),
+        );
 
-        Ok(Self {
+        Self {
             wifi: WifiStation {
                 socket_path: PATH_DEFAULT_SERVER.into(),
+                interface: None,
                 request_receiver,
                 broadcast_sender,
+                p2p_broadcast_sender,
+                raw_broadcast_sender,
+                reconnect_policy: None,
+                disconnected,
+                shutdown_ack: None,
+                coalesce_scans: true,
             },
             request_client,
             broadcast_receiver,
-        })
+        }
     }
 
     pub fn set_socket_path<S: Into<std::path::PathBuf>>(&mut self, path: S) {
         self.wifi.socket_path = path.into();
+        self.wifi.interface = None;
+        self.request_client.interface = interface_name(&self.wifi.socket_path);
     }
+
+    /// Builder form of `set_socket_path`, for sockets outside the default location
+    pub fn control_socket_path<S: Into<std::path::PathBuf>>(mut self, path: S) -> Self {
+        self.set_socket_path(path);
+        self
+    }
+
+    /// Whether `get_scan` calls made while a scan is in flight share its results rather
+    /// than requesting another scan, which wpa_supplicant would reject as busy. On by default.
+    pub fn coalesce_scans(mut self, enabled: bool) -> Self {
+        self.wifi.coalesce_scans = enabled;
+        self
+    }
+
+    /// Manages the given interface, eg: `wlan0`, through its control socket in the
+    /// default wpa_supplicant directory. Use `set_socket_path` for other locations.
+    /// Events prefixed with the name of another interface, eg: `IFNAME=wlan1`, are then
+    /// dropped, whereas other socket paths forward the events of every interface.
+    pub fn set_interface(&mut self, ifname: &str) {
+        self.set_socket_path(std::path::Path::new(SOCKET_DIR_DEFAULT).join(ifname));
+        self.wifi.interface = Some(ifname.to_string());
+    }
+
+    /// Enables reconnecting to the control socket when it breaks. While disconnected,
+    /// requests fail with `Error::ControlSocketDisconnected` and `Broadcast::Ready` is sent
+    /// again once reconnected. Disabled by default, in which case the runtime returns.
+    pub fn set_reconnect_policy(&mut self, policy: ReconnectPolicy) {
+        self.wifi.reconnect_policy = Some(policy);
+    }
+
     pub fn get_broadcast_receiver(&self) -> BroadcastReceiver {
         self.wifi.broadcast_sender.subscribe()
     }
+
+    /// Subscribes to P2P (Wi-Fi Direct) events, which have a channel of their own
+    pub fn get_p2p_broadcast_receiver(&self) -> P2pBroadcastReceiver {
+        self.wifi.p2p_broadcast_sender.subscribe()
+    }
+
+    /// Subscribes to every unsolicited event line of the interface as received, eg:
+    /// `<3>CTRL-EVENT-SCAN-STARTED `, including those without a `Broadcast`. Meant for
+    /// logging or forwarding events the typed channel doesn't model.
+    pub fn raw_events(&self) -> RawEventReceiver {
+        self.wifi.raw_broadcast_sender.subscribe()
+    }
+
+    /// Subscribes to broadcasts as a `Stream`
+    pub fn broadcast_stream(&self) -> stream::BroadcastStream<Broadcast> {
+        self.get_broadcast_receiver().into()
+    }
+
+    /// Subscribes to the broadcasts of the given kinds only, eg:
+    /// `&[BroadcastKind::Connected, BroadcastKind::Disconnected]`. Like `broadcast_stream`,
+    /// a lagging subscription skips the missed events and carries on.
+    pub fn broadcast_filtered(
+        &self,
+        kinds: &[BroadcastKind],
+    ) -> impl stream::Stream<Item = Broadcast> {
+        let kinds = kinds.to_vec();
+        self.broadcast_stream()
+            .filter(move |broadcast| kinds.contains(&broadcast.kind()))
+    }
     pub fn get_request_client(&self) -> RequestClient {
         self.request_client.clone()
     }
@@ -48,3 +154,135 @@ impl<const C: usize, const B: usize> WifiSetupGeneric<C, B> {
         self.wifi
     }
 }
+
+/// Gathers the whole station configuration in one place, producing the request client,
+/// a broadcast receiver and the runtime to spawn. Unlike `WifiSetup`, channel capacities
+/// are chosen at runtime rather than as const generics.
+#[derive(Debug, Clone)]
+pub struct StationBuilder {
+    socket_path: std::path::PathBuf,
+    interface: Option<String>,
+    request_timeout: tokio::time::Duration,
+    channel_capacity: usize,
+    broadcast_capacity: usize,
+    reconnect_policy: Option<ReconnectPolicy>,
+    coalesce_scans: bool,
+}
+
+impl Default for StationBuilder {
+    fn default() -> Self {
+        Self {
+            socket_path: PATH_DEFAULT_SERVER.into(),
+            interface: None,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            channel_capacity: 32,
+            broadcast_capacity: 32,
+            reconnect_policy: None,
+            coalesce_scans: true,
+        }
+    }
+}
+
+impl StationBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Path of the wpa_supplicant control socket, see `WifiSetup::set_socket_path`
+    pub fn socket_path<S: Into<std::path::PathBuf>>(mut self, path: S) -> Self {
+        self.socket_path = path.into();
+        self.interface = None;
+        self
+    }
+
+    /// Controls the interface through its socket in the default directory, eg: `wlan0`,
+    /// see `WifiSetup::set_interface`
+    pub fn interface(mut self, ifname: &str) -> Self {
+        self.interface = Some(ifname.to_string());
+        self
+    }
+
+    /// How long the client waits for responses, see `RequestClient::with_timeout`
+    pub fn request_timeout(mut self, timeout: tokio::time::Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Number of requests queued before clients wait for the runtime, 32 by default, eg:
+    /// more to absorb a signal poller alongside user commands. That wait isn't counted
+    /// against the request timeout. Replaces the `C` parameter of `WifiSetupGeneric`, and
+    /// `build` panics if it is zero.
+    pub fn channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity;
+        self
+    }
+
+    /// Number of broadcasts kept for slow receivers before they lag, 32 by default.
+    /// `build` panics if it is zero.
+    pub fn broadcast_capacity(mut self, capacity: usize) -> Self {
+        self.broadcast_capacity = capacity;
+        self
+    }
+
+    /// Reconnects to the control socket when it breaks, see
+    /// `WifiSetup::set_reconnect_policy`
+    pub fn auto_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = Some(policy);
+        self
+    }
+
+    /// See `WifiSetup::coalesce_scans`
+    pub fn coalesce_scans(mut self, enabled: bool) -> Self {
+        self.coalesce_scans = enabled;
+        self
+    }
+
+    /// The request client, a broadcast receiver and the runtime, whose `run` future
+    /// must be driven for requests to be answered
+    pub fn build(self) -> (RequestClient, BroadcastReceiver, WifiStation) {
+        let mut setup = WifiSetup::with_capacities(self.channel_capacity, self.broadcast_capacity);
+        match &self.interface {
+            Some(ifname) => setup.set_interface(ifname),
+            None => setup.set_socket_path(self.socket_path),
+        }
+        if let Some(policy) = self.reconnect_policy {
+            setup.set_reconnect_policy(policy);
+        }
+        let setup = setup.coalesce_scans(self.coalesce_scans);
+        let request_client = setup
+            .get_request_client()
+            .with_timeout(self.request_timeout);
+        let broadcast_receiver = setup.get_broadcast_receiver();
+        (request_client, broadcast_receiver, setup.complete())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_capacity_bounds_queued_requests() {
+        let setup = WifiSetupGeneric::<2, 32>::new().unwrap();
+        let client = setup.get_request_client();
+        let raw = || Request::Raw("PING".into(), oneshot::channel().0);
+        assert!(client.sender.try_send(raw()).is_ok());
+        assert!(client.sender.try_send(raw()).is_ok());
+        assert!(matches!(
+            client.sender.try_send(raw()),
+            Err(mpsc::error::TrySendError::Full(_))
+        ));
+    }
+
+    #[test]
+    fn builder_channel_capacity_bounds_queued_requests() {
+        let (client, _broadcast_receiver, _station) =
+            StationBuilder::new().channel_capacity(1).build();
+        let raw = || Request::Raw("PING".into(), oneshot::channel().0);
+        assert!(client.sender.try_send(raw()).is_ok());
+        assert!(matches!(
+            client.sender.try_send(raw()),
+            Err(mpsc::error::TrySendError::Full(_))
+        ));
+    }
+}
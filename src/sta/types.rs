@@ -1,67 +1,608 @@
 use super::{error, warn, Result};
-use serde::Serialize;
+use crate::error::parse_value;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 use std::str::FromStr;
 use tokio::net::UnixDatagram;
 
-#[derive(Serialize, Debug, Clone)]
+/// Maps a center frequency in MHz to its 2.4, 5 or 6 GHz channel number
+pub(crate) fn frequency_to_channel(freq: u32) -> Option<u16> {
+    let channel = match freq {
+        2484 => 14,
+        2412..=2472 if (freq - 2407).is_multiple_of(5) => (freq - 2407) / 5,
+        5180..=5885 if freq.is_multiple_of(5) && is_5ghz_channel((freq - 5000) / 5) => {
+            (freq - 5000) / 5
+        }
+        5935 => 2,
+        5955..=7115 if freq.is_multiple_of(5) && ((freq - 5950) / 5) % 4 == 1 => (freq - 5950) / 5,
+        _ => return None,
+    };
+    Some(channel as u16)
+}
+
+/// Whether a 5 GHz channel number has a 20 MHz center: 36 to 64, 100 to 144 and 149 to
+/// 177, every 4 channels
+fn is_5ghz_channel(channel: u32) -> bool {
+    match channel {
+        36..=64 | 100..=144 => channel.is_multiple_of(4),
+        149..=177 => channel % 4 == 1,
+        _ => false,
+    }
+}
+
+/// Frequency band of a WiFi channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Band {
+    TwoPointFour,
+    Five,
+    Six,
+}
+
+impl Band {
+    /// Band of a frequency in MHz, or `None` outside the 2.4, 5 and 6 GHz bands
+    pub fn from_frequency(freq: u32) -> Option<Band> {
+        match freq {
+            2400..=2500 => Some(Band::TwoPointFour),
+            5150..=5895 => Some(Band::Five),
+            5925..=7125 => Some(Band::Six),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Band {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let band = match self {
+            Band::TwoPointFour => "2.4 GHz",
+            Band::Five => "5 GHz",
+            Band::Six => "6 GHz",
+        };
+        write!(f, "{band}")
+    }
+}
+
+/// Bandwidth of the operating channel, as reported by `SIGNAL_POLL`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ChannelWidth {
+    /// 20 MHz without HT, eg: 802.11a/b/g
+    Mhz20NoHt,
+    Mhz20,
+    Mhz40,
+    Mhz80,
+    /// Two non-contiguous 80 MHz segments
+    Mhz80Plus80,
+    Mhz160,
+    Mhz320,
+}
+
+impl ChannelWidth {
+    /// Parses the `WIDTH` field, `None` for widths the driver reports as unknown
+    fn from_signal_poll(width: &str) -> Option<ChannelWidth> {
+        match width {
+            "20 MHz (no HT)" => Some(ChannelWidth::Mhz20NoHt),
+            "20 MHz" => Some(ChannelWidth::Mhz20),
+            "40 MHz" => Some(ChannelWidth::Mhz40),
+            "80 MHz" => Some(ChannelWidth::Mhz80),
+            "80+80 MHz" => Some(ChannelWidth::Mhz80Plus80),
+            "160 MHz" => Some(ChannelWidth::Mhz160),
+            "320 MHz" => Some(ChannelWidth::Mhz320),
+            _ => None,
+        }
+    }
+
+    /// Total bandwidth in MHz
+    pub fn mhz(&self) -> u32 {
+        match self {
+            ChannelWidth::Mhz20NoHt | ChannelWidth::Mhz20 => 20,
+            ChannelWidth::Mhz40 => 40,
+            ChannelWidth::Mhz80 => 80,
+            ChannelWidth::Mhz80Plus80 | ChannelWidth::Mhz160 => 160,
+            ChannelWidth::Mhz320 => 320,
+        }
+    }
+}
+
+/// What wpa_supplicant and the driver support, as reported by `GET_CAPABILITY`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Capabilities {
+    pub key_mgmt: Vec<String>,
+    pub pairwise: Vec<String>,
+    pub group: Vec<String>,
+    pub proto: Vec<String>,
+    pub auth_alg: Vec<String>,
+    pub eap: Vec<String>,
+    /// Channel numbers by hardware mode, eg: `G` for 2.4 GHz or `A` for 5 GHz. Drivers
+    /// listing a mode more than once, eg: `A` for both 5 GHz and 6 GHz, get its channels
+    /// appended in the order reported.
+    pub channels: HashMap<String, Vec<u16>>,
+}
+
+impl Capabilities {
+    /// The `GET_CAPABILITY` kinds queried, in the order `from_responses` expects them
+    pub(crate) const KINDS: [&'static str; 7] = [
+        "key_mgmt", "pairwise", "group", "proto", "auth_alg", "eap", "channels",
+    ];
+
+    pub(crate) fn from_responses(responses: &[String]) -> Result<Capabilities> {
+        let list = |index: usize| {
+            responses[index]
+                .split_whitespace()
+                .map(str::to_string)
+                .collect()
+        };
+        let mut channels = HashMap::new();
+        // eg: "Mode[G] Channels: 1 2 3"
+        for line in responses[6].lines() {
+            let Some((mode, line_channels)) = line.split_once(" Channels:") else {
+                continue;
+            };
+            let mode = mode.trim_start_matches("Mode[").trim_end_matches(']');
+            let line_channels: Vec<u16> = line_channels
+                .split_whitespace()
+                .map(|channel| parse_value("GET_CAPABILITY channels", line, channel))
+                .collect::<Result<_>>()?;
+            channels
+                .entry(mode.to_string())
+                .or_insert_with(Vec::new)
+                .extend(line_channels);
+        }
+        Ok(Capabilities {
+            key_mgmt: list(0),
+            pairwise: list(1),
+            group: list(2),
+            proto: list(3),
+            auth_alg: list(4),
+            eap: list(5),
+            channels,
+        })
+    }
+}
+
+/// Signal strength at or below which `signal_percent` reports 0%, in dBm
+const SIGNAL_FLOOR: isize = -90;
+/// Signal strength at or above which `signal_percent` reports 100%, in dBm
+const SIGNAL_CEILING: isize = -30;
+
+/// Coarse signal strength, eg: for a "bars" indicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SignalQuality {
+    Weak,
+    Fair,
+    Good,
+    Excellent,
+}
+
+impl SignalQuality {
+    /// Quality of a signal strength in dBm: excellent from -50, good from -60, fair
+    /// from -70 and weak below
+    pub fn from_dbm(dbm: isize) -> SignalQuality {
+        match dbm {
+            -50.. => SignalQuality::Excellent,
+            -60..=-51 => SignalQuality::Good,
+            -70..=-61 => SignalQuality::Fair,
+            _ => SignalQuality::Weak,
+        }
+    }
+}
+
+/// Serializes bracketed flags such as `[WPA2-PSK-CCMP][ESS]` as a list of flags
+#[cfg(feature = "serde")]
+fn serialize_flags<S: Serializer>(
+    flags: &str,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_seq(flags.split(['[', ']']).filter(|flag| !flag.is_empty()))
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_flags<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<String, D::Error> {
+    let flags = Vec::<String>::deserialize(deserializer)?;
+    Ok(flags.iter().map(|flag| format!("[{flag}]")).collect())
+}
+
+/// Hex encodes a value for commands that accept hex in place of a quoted string
+pub(crate) fn hex_encode<B: AsRef<[u8]>>(value: B) -> String {
+    value.as_ref().iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Decodes an SSID as wpa_supplicant prints it, eg: in scan results, where `\\`, `\"`,
+/// `\n`, `\r`, `\t` and `\e` escape the usual characters and `\xHH` any other byte outside
+/// printable ASCII, eg: those of emojis. Invalid escapes are kept verbatim.
+pub fn decode_ssid(escaped: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut rest = escaped.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        let decoded = match rest.first() {
+            Some(b'\\') => Some(b'\\'),
+            Some(b'"') => Some(b'"'),
+            Some(b'n') => Some(b'\n'),
+            Some(b'r') => Some(b'\r'),
+            Some(b't') => Some(b'\t'),
+            Some(b'e') => Some(0x1b),
+            Some(b'x') => rest
+                .get(1..3)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                let escape_len = if rest[0] == b'x' { 3 } else { 1 };
+                rest = &rest[escape_len..];
+            }
+            None => bytes.push(byte),
+        }
+    }
+    bytes
+}
+
+/// SSIDs are 1 to 32 bytes
+pub(crate) fn validate_ssid(ssid: &str) -> Result {
+    if ssid.is_empty() || ssid.len() > 32 {
+        return Err(error::Error::InvalidSsid(ssid.to_string()));
+    }
+    Ok(())
+}
+
+/// Whether an SSID can't be sent as a quoted string and has to be hex encoded
+pub(crate) fn ssid_needs_hex(ssid: &str) -> bool {
+    ssid.chars()
+        .any(|c| c == '"' || c == '\\' || c.is_control())
+}
+
+/// Whether a PSK is a raw 256-bit key, ie: 64 hex digits, rather than a passphrase
+pub(crate) fn is_raw_psk(psk: &str) -> bool {
+    psk.len() == 64 && psk.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Passphrases are 8 to 63 printable ASCII characters, raw keys 64 hex digits
+pub(crate) fn validate_psk(psk: &str) -> Result {
+    let passphrase =
+        (8..=63).contains(&psk.len()) && psk.bytes().all(|b| (0x20..0x7f).contains(&b));
+    if !passphrase && !is_raw_psk(psk) {
+        return Err(error::Error::InvalidPsk);
+    }
+    Ok(())
+}
+
+/// A BSSID or any other MAC address, formatted as lowercase colon-separated hex,
+/// eg: `02:00:00:00:01:00`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bssid(pub [u8; 6]);
+
+impl Bssid {
+    /// The all-zero address, which wpa_supplicant treats as "any BSSID"
+    pub const ANY: Bssid = Bssid([0; 6]);
+}
+
+impl From<[u8; 6]> for Bssid {
+    fn from(bytes: [u8; 6]) -> Bssid {
+        Bssid(bytes)
+    }
+}
+
+impl From<Bssid> for [u8; 6] {
+    fn from(bssid: Bssid) -> [u8; 6] {
+        bssid.0
+    }
+}
+
+impl std::fmt::Display for Bssid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{a:02x}:{b:02x}:{c:02x}:{d:02x}:{e:02x}:{g:02x}")
+    }
+}
+
+impl FromStr for Bssid {
+    type Err = error::Error;
+
+    fn from_str(mac: &str) -> Result<Bssid> {
+        let invalid = || error::Error::InvalidBssid(mac.to_string());
+        let mut bytes = [0; 6];
+        let mut octets = mac.split(':');
+        for byte in bytes.iter_mut() {
+            let octet = octets.next().ok_or_else(invalid)?;
+            if octet.len() != 2 {
+                return Err(invalid());
+            }
+            *byte = u8::from_str_radix(octet, 16).map_err(|_| invalid())?;
+        }
+        match octets.next() {
+            Some(_) => Err(invalid()),
+            None => Ok(Bssid(bytes)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Bssid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Bssid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Bssid, D::Error> {
+        let mac = String::deserialize(deserializer)?;
+        mac.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ScanResult {
-    mac: String,
-    frequency: String,
+    pub mac: Bssid,
+    pub frequency: String,
     pub signal: isize,
-    flags: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_flags",
+            deserialize_with = "deserialize_flags"
+        )
+    )]
+    pub flags: String,
+    /// SSID as wpa_supplicant prints it, with bytes outside printable ASCII escaped, see
+    /// `ssid_bytes`
     pub name: String,
+    /// Seconds since the BSS was last seen, as wpa_supplicant keeps results of earlier
+    /// scans. `None` if the follow-up `BSS RANGE=ALL` query failed or didn't cover it.
+    pub age: Option<u32>,
 }
 
 impl ScanResult {
     pub fn vec_from_str(response: &str) -> Result<Vec<ScanResult>> {
         let mut results = Vec::new();
         let split = response.split('\n').skip(1);
-        for line in split {
-            let mut line_split = line.split_whitespace();
+        for line in split.filter(|line| !line.trim().is_empty()) {
+            // the SSID is the last field and kept as is, whitespace included
+            let mut line_split = line.splitn(5, '\t');
             if let (Some(mac), Some(frequency), Some(signal), Some(flags)) = (
                 line_split.next(),
                 line_split.next(),
                 line_split.next(),
                 line_split.next(),
             ) {
-                let mut name: Option<String> = None;
-                for text in line_split {
-                    match &mut name {
-                        Some(started) => {
-                            started.push(' ');
-                            started.push_str(text);
-                        }
-                        None => {
-                            name = Some(text.to_string());
-                        }
-                    }
+                if let Some(name) = line_split.next().filter(|name| !name.is_empty()) {
+                    let scan_result = ScanResult {
+                        mac: parse_value("SCAN_RESULTS", line, mac)?,
+                        frequency: frequency.to_string(),
+                        signal: parse_value("SCAN_RESULTS", line, signal)?,
+                        flags: flags.to_string(),
+                        name: name.to_string(),
+                        age: None,
+                    };
+                    results.push(scan_result);
+                }
+            } else {
+                return Err(error::Error::Parse {
+                    command: "SCAN_RESULTS",
+                    line: line.to_string(),
+                    reason: "expected bssid, frequency, signal and flags".to_string(),
+                });
+            }
+        }
+        Ok(results)
+    }
+
+    /// The SSID's actual bytes, decoding the escapes wpa_supplicant prints in `name`
+    pub fn ssid_bytes(&self) -> Vec<u8> {
+        decode_ssid(&self.name)
+    }
+
+    /// The decoded SSID as text, replacing invalid UTF-8 for display
+    pub fn ssid_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.ssid_bytes()).into_owned()
+    }
+
+    /// Whether the BSS advertises SAE (WPA3-Personal), eg: `[WPA2-SAE-CCMP]`, `[RSN-SAE-CCMP]`
+    /// or the transition mode `[WPA2-PSK+SAE-CCMP]`
+    pub fn supports_sae(&self) -> bool {
+        self.flags
+            .split(['[', ']'])
+            .any(|flag| flag.split(['-', '+', '/']).any(|token| token == "SAE"))
+    }
+
+    /// The bracketed flags, eg: `[WPA2-PSK-CCMP][ESS][WPS]`, parsed
+    pub fn bss_flags(&self) -> Vec<BssFlag> {
+        self.flags
+            .split(['[', ']'])
+            .filter(|flag| !flag.is_empty())
+            .map(BssFlag::from)
+            .collect()
+    }
+
+    /// Signal strength as a percentage, scaling -90 dBm to 0% and -30 dBm to 100%
+    /// linearly so that drivers with different ranges show alike
+    pub fn signal_percent(&self) -> u8 {
+        let signal = self.signal.clamp(SIGNAL_FLOOR, SIGNAL_CEILING);
+        ((signal - SIGNAL_FLOOR) * 100 / (SIGNAL_CEILING - SIGNAL_FLOOR)) as u8
+    }
+
+    pub fn quality(&self) -> SignalQuality {
+        SignalQuality::from_dbm(self.signal)
+    }
+
+    /// Frequency of the BSS in MHz
+    pub fn frequency_mhz(&self) -> Option<u32> {
+        self.frequency.parse().ok()
+    }
+
+    /// Band the BSS operates in, `None` if its frequency is outside the WiFi bands
+    pub fn band(&self) -> Option<Band> {
+        self.frequency_mhz().and_then(Band::from_frequency)
+    }
+
+    /// Channel number of the BSS within its band, eg: 6 for 2437 MHz
+    pub fn channel(&self) -> Option<u16> {
+        self.frequency_mhz().and_then(frequency_to_channel)
+    }
+
+    /// Whether the BSS operates on a 6 GHz preferred scanning channel (5, 21, 37, ...),
+    /// where 6 GHz access points are expected to advertise themselves
+    pub fn is_psc(&self) -> bool {
+        self.band() == Some(Band::Six) && self.channel().is_some_and(|channel| channel % 16 == 5)
+    }
+
+    /// Whether the network neither uses WPA nor WEP
+    pub fn is_open(&self) -> bool {
+        !self
+            .bss_flags()
+            .iter()
+            .any(|flag| matches!(flag, BssFlag::Security { .. } | BssFlag::Wep))
+    }
+
+    /// Whether the network offers WPA3 authentication, ie: SAE or OWE
+    pub fn supports_wpa3(&self) -> bool {
+        self.bss_flags().iter().any(|flag| {
+            flag.key_mgmt()
+                .any(|key_mgmt| key_mgmt.ends_with("SAE") || key_mgmt == "OWE")
+        })
+    }
+
+    /// Whether the access point advertises WPS
+    pub fn has_wps(&self) -> bool {
+        self.bss_flags().contains(&BssFlag::Wps)
+    }
+
+    /// Whether every security suite of the network requires 802.1X authentication
+    pub fn requires_enterprise(&self) -> bool {
+        let flags = self.bss_flags();
+        let mut key_mgmt = flags.iter().flat_map(BssFlag::key_mgmt).peekable();
+        key_mgmt.peek().is_some() && key_mgmt.all(|key_mgmt| key_mgmt.contains("EAP"))
+    }
+}
+
+/// One bracketed flag of a scan result
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BssFlag {
+    /// A security suite, eg: `WPA2-PSK+SAE-CCMP` is protocol `WPA2`, key management
+    /// `PSK` and `SAE`, and cipher `CCMP`
+    Security {
+        protocol: String,
+        key_mgmt: Vec<String>,
+        ciphers: Vec<String>,
+    },
+    Wep,
+    Ess,
+    Ibss,
+    Wps,
+    P2p,
+    Mesh,
+    Hs20,
+    /// A flag this crate doesn't know about
+    Other(String),
+}
+
+impl BssFlag {
+    fn key_mgmt(&self) -> impl Iterator<Item = &str> {
+        let key_mgmt = match self {
+            BssFlag::Security { key_mgmt, .. } => key_mgmt.as_slice(),
+            _ => &[],
+        };
+        key_mgmt.iter().map(String::as_str)
+    }
+}
+
+impl From<&str> for BssFlag {
+    fn from(flag: &str) -> BssFlag {
+        match flag {
+            "WEP" => return BssFlag::Wep,
+            "ESS" => return BssFlag::Ess,
+            "IBSS" => return BssFlag::Ibss,
+            "WPS" => return BssFlag::Wps,
+            "P2P" => return BssFlag::P2p,
+            "MESH" => return BssFlag::Mesh,
+            "HS20" => return BssFlag::Hs20,
+            _ => (),
+        }
+        let mut parts = flag.splitn(3, '-');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(protocol @ ("WPA" | "WPA2" | "RSN" | "OSEN")), Some(key_mgmt), ciphers) => {
+                BssFlag::Security {
+                    protocol: protocol.to_string(),
+                    key_mgmt: key_mgmt.split('+').map(str::to_string).collect(),
+                    ciphers: ciphers
+                        .map(|ciphers| ciphers.split('+').map(str::to_string).collect())
+                        .unwrap_or_default(),
                 }
-                if let Some(name) = name {
-                    if let Ok(signal) = isize::from_str(signal) {
-                        let scan_result = ScanResult {
-                            mac: mac.to_string(),
-                            frequency: frequency.to_string(),
-                            signal,
-                            flags: flags.to_string(),
-                            name,
-                        };
-                        results.push(scan_result);
-                    } else {
-                        warn!("Invalid string for signal: {signal}");
+            }
+            _ => BssFlag::Other(flag.to_string()),
+        }
+    }
+}
+
+/// The access points of a network, as seen in one scan
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScanGroup {
+    pub ssid: String,
+    /// The BSS with the strongest signal
+    pub strongest: ScanResult,
+    /// Number of BSSIDs seen for the SSID
+    pub bss_count: usize,
+}
+
+impl ScanGroup {
+    /// Groups scan results by SSID, strongest network first
+    pub fn group(results: &[ScanResult]) -> Vec<ScanGroup> {
+        let mut groups: Vec<ScanGroup> = Vec::new();
+        for result in results {
+            match groups.iter_mut().find(|group| group.ssid == result.name) {
+                Some(group) => {
+                    group.bss_count += 1;
+                    if result.signal > group.strongest.signal {
+                        group.strongest = result.clone();
                     }
                 }
+                None => groups.push(ScanGroup {
+                    ssid: result.name.clone(),
+                    strongest: result.clone(),
+                    bss_count: 1,
+                }),
             }
         }
-        Ok(results)
+        groups.sort_by_key(|group| std::cmp::Reverse(group.strongest.signal));
+        groups
     }
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NetworkResult {
     pub network_id: usize,
     pub ssid: String,
-    flags: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_flags",
+            deserialize_with = "deserialize_flags"
+        )
+    )]
+    pub flags: String,
+    /// Whether the network is disabled, in which case wpa_supplicant won't connect to it
+    pub disabled: bool,
+    /// Selection priority, higher values are preferred
+    pub priority: i32,
+    /// BSSID the network is pinned to, see `RequestClient::select_bssid`
+    pub bssid: Option<Bssid>,
 }
 
 impl NetworkResult {
@@ -75,21 +616,38 @@ impl NetworkResult {
         for line in split {
             let mut line_split = line.split_whitespace();
             if let Some(network_id) = line_split.next() {
+                let network_id: usize = parse_value("LIST_NETWORKS", line, network_id)?;
                 let cmd = format!("GET_NETWORK {network_id} ssid");
                 let bytes = cmd.into_bytes();
                 socket.send(&bytes).await?;
                 let n = socket.recv(&mut buffer).await?;
-                let ssid = std::str::from_utf8(&buffer[..n])?.trim_matches('\"');
-                if let Ok(network_id) = usize::from_str(network_id) {
-                    if let Some(flags) = line_split.last() {
-                        results.push(NetworkResult {
-                            flags: flags.into(),
-                            ssid: ssid.into(),
-                            network_id,
-                        })
-                    }
-                } else {
-                    warn!("Invalid network_id: {network_id}")
+                let ssid = std::str::from_utf8(&buffer[..n])?
+                    .trim_matches('\"')
+                    .to_string();
+                let cmd = format!("GET_NETWORK {network_id} priority");
+                socket.send(cmd.as_bytes()).await?;
+                let n = socket.recv(&mut buffer).await?;
+                let priority = std::str::from_utf8(&buffer[..n])?.trim_end();
+                let priority = i32::from_str(priority).unwrap_or_else(|_| {
+                    warn!("Invalid priority for network {network_id}: {priority}");
+                    0
+                });
+                // wpa_supplicant answers FAIL when no BSSID is configured
+                let cmd = format!("GET_NETWORK {network_id} bssid");
+                socket.send(cmd.as_bytes()).await?;
+                let n = socket.recv(&mut buffer).await?;
+                let bssid = Bssid::from_str(std::str::from_utf8(&buffer[..n])?.trim_end())
+                    .ok()
+                    .filter(|bssid| *bssid != Bssid::ANY);
+                if let Some(flags) = line_split.last() {
+                    results.push(NetworkResult {
+                        disabled: flags.contains("[DISABLED]"),
+                        flags: flags.into(),
+                        ssid,
+                        network_id,
+                        priority,
+                        bssid,
+                    })
                 }
             }
         }
@@ -97,16 +655,588 @@ impl NetworkResult {
     }
 }
 
-pub type Status = HashMap<String, String>;
+/// Details of a single BSS as reported by the `BSS` command
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BssInfo {
+    pub bssid: Bssid,
+    /// Frequency in MHz
+    pub frequency: u32,
+    /// Beacon interval in time units (1024 µs)
+    pub beacon_int: u16,
+    /// IEEE 802.11 capability information field
+    pub capabilities: u16,
+    /// Signal level in dBm
+    pub level: isize,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_flags",
+            deserialize_with = "deserialize_flags"
+        )
+    )]
+    pub flags: String,
+    pub ssid: String,
+    /// Seconds since the BSS was last seen
+    pub age: u32,
+    /// Information elements, hex encoded
+    pub ie: String,
+}
 
-pub(crate) fn parse_status(response: &str) -> Result<Status> {
-    use config::{Config, File, FileFormat};
-    let config = Config::builder()
-        .add_source(File::from_str(response, FileFormat::Ini))
-        .build()
-        .map_err(|e| error::Error::ParsingWifiStatus {
-            e,
-            s: response.into(),
+impl BssInfo {
+    /// Parses the response of `BSS`, which is empty when there is no such BSS
+    pub fn from_response(response: &str) -> Result<Option<BssInfo>> {
+        if response.trim().is_empty() {
+            return Ok(None);
+        }
+        let mut bss = BssInfo::default();
+        for line in response.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                match key {
+                    "bssid" => bss.bssid = parse_value("BSS", line, value)?,
+                    "freq" => bss.frequency = parse_value("BSS", line, value)?,
+                    "beacon_int" => bss.beacon_int = parse_value("BSS", line, value)?,
+                    "capabilities" => {
+                        bss.capabilities = u16::from_str_radix(value.trim_start_matches("0x"), 16)
+                            .map_err(|e| error::Error::Parse {
+                            command: "BSS",
+                            line: line.to_string(),
+                            reason: e.to_string(),
+                        })?
+                    }
+                    "level" => bss.level = parse_value("BSS", line, value)?,
+                    "flags" => bss.flags = value.to_string(),
+                    "ssid" => bss.ssid = value.to_string(),
+                    "age" => bss.age = parse_value("BSS", line, value)?,
+                    "ie" => bss.ie = value.to_string(),
+                    _ => (),
+                }
+            }
+        }
+        Ok(Some(bss))
+    }
+}
+
+/// Live link quality as reported by `SIGNAL_POLL`. Older supplicants omit some fields.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SignalPoll {
+    /// Signal strength in dBm
+    pub rssi: Option<i32>,
+    /// Noise level in dBm
+    pub noise: Option<i32>,
+    /// Operating frequency in MHz
+    pub frequency: Option<u32>,
+    /// Transmit bitrate in Mbps
+    pub link_speed: Option<u32>,
+    /// Averaged signal strength in dBm
+    pub avg_rssi: Option<i32>,
+    /// Bandwidth of the operating channel, `None` when older drivers don't report it
+    pub channel_width: Option<ChannelWidth>,
+    /// Center frequency in MHz of the whole channel, which differs from `frequency` on
+    /// channels wider than 20 MHz
+    pub center_freq: Option<u32>,
+    /// Center frequency in MHz of the second segment of an 80+80 MHz channel
+    pub center_freq2: Option<u32>,
+}
+
+/// Value reported by drivers that don't measure noise
+const NOISE_UNKNOWN: i32 = 9999;
+
+impl SignalPoll {
+    pub fn from_response(response: &str) -> Result<SignalPoll> {
+        let mut signal_poll = SignalPoll::default();
+        for line in response.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                match key {
+                    "RSSI" => signal_poll.rssi = Some(parse_value("SIGNAL_POLL", line, value)?),
+                    "NOISE" => {
+                        signal_poll.noise = Some(parse_value("SIGNAL_POLL", line, value)?)
+                            .filter(|n| *n != NOISE_UNKNOWN)
+                    }
+                    "FREQUENCY" => {
+                        signal_poll.frequency = Some(parse_value("SIGNAL_POLL", line, value)?)
+                    }
+                    "LINKSPEED" => {
+                        signal_poll.link_speed = Some(parse_value("SIGNAL_POLL", line, value)?)
+                    }
+                    "AVG_RSSI" => {
+                        signal_poll.avg_rssi = Some(parse_value("SIGNAL_POLL", line, value)?)
+                    }
+                    "WIDTH" => signal_poll.channel_width = ChannelWidth::from_signal_poll(value),
+                    // drivers report 0 for segments that aren't in use
+                    "CENTER_FRQ1" => {
+                        signal_poll.center_freq =
+                            Some(parse_value("SIGNAL_POLL", line, value)?).filter(|freq| *freq != 0)
+                    }
+                    "CENTER_FRQ2" => {
+                        signal_poll.center_freq2 =
+                            Some(parse_value("SIGNAL_POLL", line, value)?).filter(|freq| *freq != 0)
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(signal_poll)
+    }
+
+    /// Channel number of the operating frequency
+    pub fn channel(&self) -> Option<u16> {
+        self.frequency.and_then(frequency_to_channel)
+    }
+}
+
+/// Traffic counters of the current link. Packet counts come from `PKTCNT_POLL`, byte
+/// counts from the interface statistics in sysfs. Counters neither source reports
+/// are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LinkStats {
+    pub rx_bytes: Option<u64>,
+    pub tx_bytes: Option<u64>,
+    pub rx_packets: Option<u64>,
+    pub tx_packets: Option<u64>,
+    /// Retransmitted frames. Neither `PKTCNT_POLL` nor the sysfs statistics report them,
+    /// so this stays `None` until a source that does is read.
+    pub tx_retries: Option<u64>,
+    pub tx_failed: Option<u64>,
+}
+
+impl LinkStats {
+    pub fn from_response(response: &str) -> Result<LinkStats> {
+        let mut stats = LinkStats::default();
+        for line in response.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                match key {
+                    "TXGOOD" => stats.tx_packets = Some(parse_value("PKTCNT_POLL", line, value)?),
+                    "TXBAD" => stats.tx_failed = Some(parse_value("PKTCNT_POLL", line, value)?),
+                    "RXGOOD" => stats.rx_packets = Some(parse_value("PKTCNT_POLL", line, value)?),
+                    _ => (),
+                }
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Version of wpa_supplicant, eg: `2.10` or `2.11-devel`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SupplicantVersion {
+    pub major: u32,
+    pub minor: u32,
+    /// 0 when the version has no patch level, as is usual for releases
+    pub patch: u32,
+    /// The version as reported, including suffixes such as `-devel`
+    pub raw: String,
+}
+
+impl SupplicantVersion {
+    /// Parses the first `major.minor[.patch]` of the version, ignoring whatever surrounds
+    /// it, eg: a `v` prefix or distribution suffixes
+    pub fn from_response(response: &str) -> Result<SupplicantVersion> {
+        let raw = response.trim();
+        let numbers = raw
+            .trim_start_matches(|c: char| !c.is_ascii_digit())
+            .split(|c: char| !c.is_ascii_digit() && c != '.')
+            .next()
+            .unwrap_or_default();
+        let mut parts = numbers.split('.').map(|part| part.parse::<u32>().ok());
+        let invalid = || error::Error::Parse {
+            command: "GET version",
+            line: raw.to_string(),
+            reason: "expected major.minor".to_string(),
+        };
+        let major = parts.next().flatten().ok_or_else(invalid)?;
+        let minor = parts.next().flatten().ok_or_else(invalid)?;
+        Ok(SupplicantVersion {
+            major,
+            minor,
+            patch: parts.next().flatten().unwrap_or_default(),
+            raw: raw.to_string(),
+        })
+    }
+
+    /// Whether this is the given release or a later one, eg: `at_least(2, 10)`
+    pub fn at_least(&self, major: u32, minor: u32) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+}
+
+impl std::fmt::Display for SupplicantVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+/// dot11 and dot1x MIB variables as reported by `MIB`, eg: the EAPOL frame counters
+/// `dot1xSuppEapolFramesRx` and `dot1xSuppEapolFramesTx` or the supplicant state machine
+/// `dot1xSuppPaeState`. The variables depend on the wpa_supplicant build and the
+/// connection, so they are kept by name as reported.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Mib {
+    pub variables: HashMap<String, String>,
+}
+
+impl Mib {
+    /// Lines that aren't `key=value` are skipped rather than failing the whole response
+    pub fn from_response(response: &str) -> Mib {
+        let variables = response
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Mib { variables }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.variables.get(key).map(String::as_str)
+    }
+
+    /// A numeric variable such as a frame counter, `None` if missing or not a number
+    pub fn counter(&self, key: &str) -> Option<u64> {
+        self.get(key)?.parse().ok()
+    }
+}
+
+/// Verbosity of wpa_supplicant's own debug log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LogLevel {
+    Excessive,
+    Msgdump,
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = match self {
+            LogLevel::Excessive => "EXCESSIVE",
+            LogLevel::Msgdump => "MSGDUMP",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warning => "WARNING",
+            LogLevel::Error => "ERROR",
+        };
+        write!(f, "{level}")
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(level: &str) -> std::result::Result<LogLevel, String> {
+        match level {
+            "EXCESSIVE" => Ok(LogLevel::Excessive),
+            "MSGDUMP" => Ok(LogLevel::Msgdump),
+            "DEBUG" => Ok(LogLevel::Debug),
+            "INFO" => Ok(LogLevel::Info),
+            "WARNING" => Ok(LogLevel::Warning),
+            "ERROR" => Ok(LogLevel::Error),
+            other => Err(format!("unknown log level {other:?}")),
+        }
+    }
+}
+
+/// Logging configuration of wpa_supplicant, as reported by `LOG_LEVEL`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LogSettings {
+    pub level: LogLevel,
+    /// Whether log lines are timestamped
+    pub timestamp: bool,
+}
+
+impl LogSettings {
+    pub fn from_response(response: &str) -> Result<LogSettings> {
+        let mut level = None;
+        let mut timestamp = false;
+        for line in response.lines() {
+            if let Some((key, value)) = line.split_once(": ") {
+                match key {
+                    "Current level" => level = Some(parse_value("LOG_LEVEL", line, value)?),
+                    "Timestamp" => timestamp = value != "0",
+                    _ => (),
+                }
+            }
+        }
+        let level = level.ok_or_else(|| error::Error::Parse {
+            command: "LOG_LEVEL",
+            line: response.to_string(),
+            reason: "missing current level".to_string(),
         })?;
-    Ok(config.try_deserialize::<HashMap<String, String>>().unwrap())
+        Ok(LogSettings { level, timestamp })
+    }
+}
+
+/// State of the wpa_supplicant connection state machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WpaState {
+    Disconnected,
+    InterfaceDisabled,
+    Inactive,
+    Scanning,
+    Authenticating,
+    Associating,
+    Associated,
+    FourWayHandshake,
+    GroupHandshake,
+    Completed,
+    /// A state this crate doesn't know about
+    Unknown(String),
+}
+
+impl From<&str> for WpaState {
+    fn from(state: &str) -> WpaState {
+        match state {
+            "DISCONNECTED" => WpaState::Disconnected,
+            "INTERFACE_DISABLED" => WpaState::InterfaceDisabled,
+            "INACTIVE" => WpaState::Inactive,
+            "SCANNING" => WpaState::Scanning,
+            "AUTHENTICATING" => WpaState::Authenticating,
+            "ASSOCIATING" => WpaState::Associating,
+            "ASSOCIATED" => WpaState::Associated,
+            "4WAY_HANDSHAKE" => WpaState::FourWayHandshake,
+            "GROUP_HANDSHAKE" => WpaState::GroupHandshake,
+            "COMPLETED" => WpaState::Completed,
+            other => WpaState::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl WpaState {
+    /// Whether the station is fully connected, ie: the key handshakes have completed
+    pub fn is_connected(&self) -> bool {
+        *self == WpaState::Completed
+    }
+
+    /// Coarse progress of connecting in percent, from 0 while idle to 100 once connected,
+    /// eg: for the progress bar of an onboarding UI
+    pub fn connection_progress(&self) -> u8 {
+        match self {
+            WpaState::Disconnected
+            | WpaState::InterfaceDisabled
+            | WpaState::Inactive
+            | WpaState::Unknown(_) => 0,
+            WpaState::Scanning => 10,
+            WpaState::Authenticating => 30,
+            WpaState::Associating => 45,
+            WpaState::Associated => 60,
+            WpaState::FourWayHandshake => 75,
+            WpaState::GroupHandshake => 90,
+            WpaState::Completed => 100,
+        }
+    }
+
+    /// The state numbered as in `CTRL-EVENT-STATE-CHANGE` events
+    pub(crate) fn from_index(index: &str) -> WpaState {
+        match index {
+            "0" => WpaState::Disconnected,
+            "1" => WpaState::InterfaceDisabled,
+            "2" => WpaState::Inactive,
+            "3" => WpaState::Scanning,
+            "4" => WpaState::Authenticating,
+            "5" => WpaState::Associating,
+            "6" => WpaState::Associated,
+            "7" => WpaState::FourWayHandshake,
+            "8" => WpaState::GroupHandshake,
+            "9" => WpaState::Completed,
+            other => WpaState::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for WpaState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self {
+            WpaState::Disconnected => "DISCONNECTED",
+            WpaState::InterfaceDisabled => "INTERFACE_DISABLED",
+            WpaState::Inactive => "INACTIVE",
+            WpaState::Scanning => "SCANNING",
+            WpaState::Authenticating => "AUTHENTICATING",
+            WpaState::Associating => "ASSOCIATING",
+            WpaState::Associated => "ASSOCIATED",
+            WpaState::FourWayHandshake => "4WAY_HANDSHAKE",
+            WpaState::GroupHandshake => "GROUP_HANDSHAKE",
+            WpaState::Completed => "COMPLETED",
+            WpaState::Unknown(state) => state,
+        };
+        write!(f, "{state}")
+    }
+}
+
+/// Cipher negotiated for a connection, as named by wpa_supplicant
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Cipher {
+    None,
+    Wep40,
+    Wep104,
+    Tkip,
+    Ccmp,
+    Ccmp256,
+    Gcmp,
+    Gcmp256,
+    /// A cipher this crate doesn't know about
+    Unknown(String),
+}
+
+impl From<&str> for Cipher {
+    fn from(cipher: &str) -> Cipher {
+        match cipher {
+            "NONE" => Cipher::None,
+            "WEP-40" => Cipher::Wep40,
+            "WEP-104" => Cipher::Wep104,
+            "TKIP" => Cipher::Tkip,
+            "CCMP" => Cipher::Ccmp,
+            "CCMP-256" => Cipher::Ccmp256,
+            "GCMP" => Cipher::Gcmp,
+            "GCMP-256" => Cipher::Gcmp256,
+            other => Cipher::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Cipher {
+    /// Whether the cipher is considered broken, ie: no encryption, WEP or TKIP
+    pub fn is_weak(&self) -> bool {
+        matches!(
+            self,
+            Cipher::None | Cipher::Wep40 | Cipher::Wep104 | Cipher::Tkip
+        )
+    }
+}
+
+impl std::fmt::Display for Cipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cipher = match self {
+            Cipher::None => "NONE",
+            Cipher::Wep40 => "WEP-40",
+            Cipher::Wep104 => "WEP-104",
+            Cipher::Tkip => "TKIP",
+            Cipher::Ccmp => "CCMP",
+            Cipher::Ccmp256 => "CCMP-256",
+            Cipher::Gcmp => "GCMP",
+            Cipher::Gcmp256 => "GCMP-256",
+            Cipher::Unknown(cipher) => cipher,
+        };
+        write!(f, "{cipher}")
+    }
+}
+
+/// Protected Management Frames (802.11w) status of a connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Pmf {
+    #[default]
+    Disabled,
+    /// Management frames are protected, but the network doesn't require it
+    Optional,
+    Required,
+}
+
+/// Status of the WiFi Station, as reported by `STATUS`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Status {
+    pub wpa_state: WpaState,
+    pub ssid: Option<String>,
+    pub bssid: Option<Bssid>,
+    /// Operating frequency in MHz
+    pub freq: Option<u32>,
+    pub ip_address: Option<String>,
+    pub key_mgmt: Option<String>,
+    pub pairwise_cipher: Option<Cipher>,
+    pub group_cipher: Option<Cipher>,
+    /// Management frame protection in use on the current connection
+    pub pmf: Pmf,
+    /// Every other field reported by wpa_supplicant
+    pub extra: HashMap<String, String>,
+}
+
+impl Status {
+    pub fn from_response(response: &str) -> Result<Status> {
+        let mut extra = HashMap::new();
+        for line in response.lines() {
+            match line.split_once('=') {
+                Some((key, value)) => extra.insert(key.to_string(), value.to_string()),
+                None => {
+                    return Err(error::Error::Parse {
+                        command: "STATUS",
+                        line: line.to_string(),
+                        reason: "expected key=value".to_string(),
+                    })
+                }
+            };
+        }
+        let freq = extra
+            .remove("freq")
+            .map(|freq| parse_value("STATUS", &format!("freq={freq}"), &freq))
+            .transpose()?;
+        let bssid = extra
+            .remove("bssid")
+            .map(|bssid| parse_value("STATUS", &format!("bssid={bssid}"), &bssid))
+            .transpose()?;
+        let pmf = match extra.remove("pmf").as_deref() {
+            None | Some("0") => Pmf::Disabled,
+            Some("1") => Pmf::Optional,
+            Some("2") => Pmf::Required,
+            Some(other) => {
+                return Err(error::Error::Parse {
+                    command: "STATUS",
+                    line: format!("pmf={other}"),
+                    reason: "expected 0, 1 or 2".to_string(),
+                })
+            }
+        };
+        Ok(Status {
+            wpa_state: WpaState::from(extra.remove("wpa_state").unwrap_or_default().as_str()),
+            ssid: extra.remove("ssid"),
+            bssid,
+            freq,
+            ip_address: extra.remove("ip_address"),
+            key_mgmt: extra.remove("key_mgmt"),
+            pairwise_cipher: extra.remove("pairwise_cipher").as_deref().map(Cipher::from),
+            group_cipher: extra.remove("group_cipher").as_deref().map(Cipher::from),
+            pmf,
+            extra,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequency_to_channel_follows_the_channel_raster() {
+        assert_eq!(frequency_to_channel(2412), Some(1));
+        assert_eq!(frequency_to_channel(2484), Some(14));
+        assert_eq!(frequency_to_channel(5180), Some(36));
+        assert_eq!(frequency_to_channel(5320), Some(64));
+        assert_eq!(frequency_to_channel(5500), Some(100));
+        assert_eq!(frequency_to_channel(5720), Some(144));
+        assert_eq!(frequency_to_channel(5745), Some(149));
+        assert_eq!(frequency_to_channel(5885), Some(177));
+        assert_eq!(frequency_to_channel(5955), Some(1));
+        for freq in [5155, 5160, 5170, 5340, 5480, 5740, 5890, 5895] {
+            assert_eq!(frequency_to_channel(freq), None, "{freq} MHz");
+        }
+    }
+
+    #[test]
+    fn capabilities_merge_repeated_modes() {
+        let mut responses = vec![String::new(); Capabilities::KINDS.len()];
+        responses[6] =
+            "Mode[G] Channels: 1 6 11\nMode[A] Channels: 36 40\nMode[A] Channels: 40 1 5"
+                .to_string();
+        let capabilities = Capabilities::from_responses(&responses).unwrap();
+        assert_eq!(capabilities.channels["G"], vec![1, 6, 11]);
+        assert_eq!(capabilities.channels["A"], vec![36, 40, 40, 1, 5]);
+    }
 }
@@ -0,0 +1,36 @@
+use super::*;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+pub use tokio_stream::Stream;
+
+/// Broadcast receiver adapted into a `Stream`, to be used with `StreamExt` combinators.
+/// A lagging receiver skips the missed events rather than ending the stream.
+pub struct BroadcastStream<T> {
+    inner: tokio_stream::wrappers::BroadcastStream<T>,
+}
+
+impl<T: 'static + Clone + Send> From<broadcast::Receiver<T>> for BroadcastStream<T> {
+    fn from(receiver: broadcast::Receiver<T>) -> Self {
+        Self {
+            inner: tokio_stream::wrappers::BroadcastStream::new(receiver),
+        }
+    }
+}
+
+impl<T: 'static + Clone + Send> Stream for BroadcastStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        loop {
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(item))) => return Poll::Ready(Some(item)),
+                Poll::Ready(Some(Err(BroadcastStreamRecvError::Lagged(n)))) => {
+                    warn!("Broadcast stream lagged, skipped {n} events");
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}